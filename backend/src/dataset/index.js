@@ -17,6 +17,8 @@ const { HierarchicalNSW } = hnswlib;
  * @property {string} v
  * @property {number} dimen
  * @property {import('hnswlib-node').SpaceName} metric
+ * @property {import('../quantization.js').Quantization=} quantization
+ *   present if the index was built from quantized features, which it holds dequantized
  */
 
 /**
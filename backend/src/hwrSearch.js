@@ -1,6 +1,7 @@
 // @ts-check
 
 import { datasetMeta, glyphNames, hnsw } from './dataset/index.js';
+import { dequantize, isValidCode } from './quantization.js';
 
 /**
  * @typedef SearchResult
@@ -14,7 +15,9 @@ import { datasetMeta, glyphNames, hnsw } from './dataset/index.js';
  */
 const performSearch = (query) => {
   const numNeighbors = 20;
-  query = resizeQuery(query, datasetMeta.dimen);
+  query = datasetMeta.quantization
+    ? dequantize(datasetMeta.quantization, query)
+    : resizeQuery(query, datasetMeta.dimen);
   const { neighbors, distances } = hnsw.searchKnn(query, numNeighbors);
   if (neighbors.length !== distances.length) {
     throw new Error('neighbors and distances have different length');
@@ -35,6 +38,7 @@ export const warmup = async () => {
     dumpTime: datasetMeta.dumpTime,
     numItems: datasetMeta.numItems,
     v: datasetMeta.v,
+    quantization: datasetMeta.quantization,
   };
 };
 
@@ -58,6 +62,9 @@ export class InvalidVError extends Error {
 }
 
 /**
+ * The query is the feature as space-separated numbers. If the index was built from quantized
+ * features, it must be the quantized codes of the whole feature instead (see `quantization` of
+ * the warmup response), which are dequantized before the search.
  * @param {unknown} queryStr
  * @return {number[] | null}
  */
@@ -69,6 +76,15 @@ export const parseQuery = (queryStr) => {
   if (query.some((item) => isNaN(item) || !Number.isFinite(item))) {
     return null;
   }
+  const { quantization } = datasetMeta;
+  if (quantization) {
+    if (
+      query.length !== datasetMeta.dimen ||
+      query.some((item) => !isValidCode(quantization.format, item))
+    ) {
+      return null;
+    }
+  }
   return query;
 };
 
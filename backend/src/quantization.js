@@ -0,0 +1,57 @@
+// @ts-check
+
+// Also imported by build_index/build_feature.js, which dequantizes the features of an index
+// the same way as the backend dequantizes queries.
+
+/**
+ * Mapping of quantized codes to feature values: `value = code * scale + offset`, where the
+ * code of the f16 format is the half-precision float of its bit pattern.
+ * @typedef Quantization
+ * @property {'u8' | 'f16'} format
+ * @property {number} scale
+ * @property {number} offset
+ */
+
+/**
+ * @param {number} bits
+ * @returns {number}
+ */
+export const f16BitsToNumber = (bits) => {
+  const sign = bits & 0x8000 ? -1 : 1;
+  const exponent = (bits >> 10) & 0x1f;
+  const fraction = bits & 0x3ff;
+  if (exponent === 0) {
+    return sign * 2 ** -14 * (fraction / 1024);
+  }
+  if (exponent === 0x1f) {
+    return fraction ? NaN : sign * Infinity;
+  }
+  return sign * 2 ** (exponent - 15) * (1 + fraction / 1024);
+};
+
+/**
+ * Whether `code` is a code of `format` standing for a finite value.
+ * @param {Quantization['format']} format
+ * @param {number} code
+ * @returns {boolean}
+ */
+export const isValidCode = (format, code) => {
+  if (!Number.isInteger(code) || code < 0) {
+    return false;
+  }
+  if (format === 'f16') {
+    // all exponent bits set: infinity or NaN
+    return code <= 0xffff && (code & 0x7c00) !== 0x7c00;
+  }
+  return code <= 0xff;
+};
+
+/**
+ * @param {Quantization} quantization
+ * @param {number[]} codes
+ * @returns {number[]}
+ */
+export const dequantize = ({ format, scale, offset }, codes) => {
+  const values = format === 'f16' ? codes.map(f16BitsToNumber) : codes;
+  return values.map((value) => value * scale + offset);
+};
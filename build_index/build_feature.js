@@ -9,6 +9,8 @@ import { parseArgs } from "node:util";
 import hnswlib from "hnswlib-node";
 const { HierarchicalNSW } = hnswlib;
 
+import { dequantize } from "../backend/src/quantization.js";
+
 const { positionals } = parseArgs({
   strict: true,
   allowPositionals: true,
@@ -70,19 +72,26 @@ const inputRL = createInterface({
 });
 for await (const line of inputRL) {
   if (!metadata) {
-    const [timestamp_str, v, dimen_str, len_hint_str] = line.split(" ");
+    const [timestamp_str, v, dimen_str, len_hint_str, ...extra_strs] =
+      line.split(" ");
     const dimen = +dimen_str;
     metadata = {
       dumpTime: +timestamp_str,
       v,
       dimen,
+      ...(extra_strs.length > 0 && JSON.parse(extra_strs.join(" "))),
     };
     hnsw = new HierarchicalNSW(modelMetric, dimen);
     hnsw.initIndex(+len_hint_str);
     continue;
   }
   const [name, feature_str] = line.split(" ");
-  const feature = feature_str.split(",").map((s) => +s);
+  const values = feature_str.split(",").map((s) => +s);
+  // quantized features are indexed dequantized; the mapping stays in the metadata so that the
+  // backend can take queries quantized the same way
+  const feature = metadata.quantization
+    ? dequantize(metadata.quantization, values)
+    : values;
   outputFeature(name, feature);
 }
 
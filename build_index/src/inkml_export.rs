@@ -0,0 +1,35 @@
+use std::io::{self, Write};
+use std::path::Path;
+
+use gwtegaki_model::InkmlWriter;
+use indicatif::ProgressBar;
+
+use crate::dump_reader::Dump;
+use crate::glyph_name::is_target_glyph_name;
+use crate::kage::{BuhinRecurser, kage_is_alias};
+
+/// Writes the strokes of every target glyph as a single InkML document, one trace group per
+/// glyph labeled with its name.
+pub fn export_inkml(dumpfilepath: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let dump = Dump::read_from_file(dumpfilepath)?;
+
+    let mut writer = InkmlWriter::new(io::BufWriter::new(io::stdout()))?;
+    let pb = ProgressBar::new(dump.len().try_into().unwrap());
+    for (name, data) in dump.iter() {
+        pb.inc(1);
+
+        if kage_is_alias(data) || !is_target_glyph_name(name) {
+            continue;
+        }
+        let mut recurser = BuhinRecurser::new();
+        let strokes = recurser.kage_data_to_strokes(data, &dump);
+        if strokes.is_empty() {
+            continue;
+        }
+        writer.write_labeled_strokes(name, &strokes)?;
+    }
+    writer.finish()?.flush()?;
+    pb.finish();
+
+    Ok(())
+}
@@ -1,4 +1,4 @@
-use gwtegaki_model::{Point, Stroke};
+use gwtegaki_model::{Point, Stroke, cubic_bezier_stroke, quadratic_bezier_stroke};
 
 use crate::dump_reader::Dump;
 
@@ -27,7 +27,8 @@ impl BuhinRecurser {
         self.stack.pop();
     }
 
-    fn kage_line_to_strokes(&mut self, line: &str, dump: &Dump) -> Vec<Stroke> {
+    /// Strokes of one line of KAGE data, each with the KAGE stroke type it was drawn from.
+    fn kage_line_to_strokes(&mut self, line: &str, dump: &Dump) -> Vec<(i32, Stroke)> {
         let numeric_data: Vec<f64> = line
             .split(':')
             .map(parse_cell)
@@ -35,38 +36,38 @@ impl BuhinRecurser {
             .take(11)
             .collect();
 
-        match line
+        let stroke_type = line
             .split(':')
             .nth(0)
             .and_then(|s| s.parse::<i32>().ok())
-            .unwrap_or(0)
-        {
-            1 => vec![line_stroke(
+            .unwrap_or(0);
+        let stroke = match stroke_type {
+            1 => line_stroke(
                 (numeric_data[3], numeric_data[4]),
                 (numeric_data[5], numeric_data[6]),
-            )],
-            2 => vec![quadratic_bezier_stroke(
+            ),
+            2 => quadratic_bezier_stroke(
+                (numeric_data[3], numeric_data[4]).into(),
+                (numeric_data[5], numeric_data[6]).into(),
+                (numeric_data[7], numeric_data[8]).into(),
+            ),
+            3 | 4 => bend_stroke(
                 (numeric_data[3], numeric_data[4]),
                 (numeric_data[5], numeric_data[6]),
                 (numeric_data[7], numeric_data[8]),
-            )],
-            3 | 4 => vec![bend_stroke(
-                (numeric_data[3], numeric_data[4]),
-                (numeric_data[5], numeric_data[6]),
-                (numeric_data[7], numeric_data[8]),
-            )],
-            6 => vec![cubic_bezier_stroke(
+            ),
+            6 => cubic_bezier_stroke(
+                (numeric_data[3], numeric_data[4]).into(),
+                (numeric_data[5], numeric_data[6]).into(),
+                (numeric_data[7], numeric_data[8]).into(),
+                (numeric_data[9], numeric_data[10]).into(),
+            ),
+            7 => slash_stroke(
                 (numeric_data[3], numeric_data[4]),
                 (numeric_data[5], numeric_data[6]),
                 (numeric_data[7], numeric_data[8]),
                 (numeric_data[9], numeric_data[10]),
-            )],
-            7 => vec![slash_stroke(
-                (numeric_data[3], numeric_data[4]),
-                (numeric_data[5], numeric_data[6]),
-                (numeric_data[7], numeric_data[8]),
-                (numeric_data[9], numeric_data[10]),
-            )],
+            ),
             99 => {
                 let strokes = {
                     let Some(part_name) = line.split(':').nth(7) else {
@@ -79,21 +80,33 @@ impl BuhinRecurser {
                     if self.enter(part_name).is_err() {
                         return vec![];
                     }
-                    let strokes = self.kage_data_to_strokes(part_data, dump);
+                    let strokes = self.kage_data_to_typed_strokes(part_data, dump);
                     self.exit();
                     strokes
                 };
+                let (stroke_types, strokes): (Vec<_>, Vec<_>) = strokes.into_iter().unzip();
                 let point_s = (numeric_data[1], numeric_data[2]);
                 let point_0 = (numeric_data[3], numeric_data[4]);
                 let point_1 = (numeric_data[5], numeric_data[6]);
                 let point_t = (numeric_data[9], numeric_data[10]);
-                transform_buhin_strokes(strokes, point_s, point_0, point_1, point_t)
+                let strokes = transform_buhin_strokes(strokes, point_s, point_0, point_1, point_t);
+                return stroke_types.into_iter().zip(strokes).collect();
             }
-            _ => vec![],
-        }
+            _ => return vec![],
+        };
+        vec![(stroke_type, stroke)]
     }
 
     pub fn kage_data_to_strokes(&mut self, data: &str, dump: &Dump) -> Vec<Stroke> {
+        self.kage_data_to_typed_strokes(data, dump)
+            .into_iter()
+            .map(|(_, stroke)| stroke)
+            .collect()
+    }
+
+    /// Like [`Self::kage_data_to_strokes`], with the KAGE stroke type (1, 2, 3, 4, 6 or 7) of
+    /// each stroke.
+    pub fn kage_data_to_typed_strokes(&mut self, data: &str, dump: &Dump) -> Vec<(i32, Stroke)> {
         data.split('$')
             .flat_map(|line| self.kage_line_to_strokes(line, dump))
             .collect()
@@ -109,46 +122,6 @@ fn line_stroke(start: (f64, f64), end: (f64, f64)) -> Stroke {
     Stroke(vec![start.into(), end.into()])
 }
 
-fn quadratic_bezier_stroke(start: (f64, f64), control: (f64, f64), end: (f64, f64)) -> Stroke {
-    const N_SAMPLES: i32 = 4;
-    let mut points: Vec<Point> = vec![start.into()];
-    let (sx, sy) = start;
-    let (cx, cy) = control;
-    let (ex, ey) = end;
-    for i in 1..=N_SAMPLES {
-        let t = i as f64 / (N_SAMPLES + 1) as f64;
-        let s = 1.0 - t;
-        let x = s * s * sx + 2.0 * s * t * cx + t * t * ex;
-        let y = s * s * sy + 2.0 * s * t * cy + t * t * ey;
-        points.push((x, y).into());
-    }
-    points.push(end.into());
-    Stroke(points)
-}
-
-fn cubic_bezier_stroke(
-    start: (f64, f64),
-    control1: (f64, f64),
-    control2: (f64, f64),
-    end: (f64, f64),
-) -> Stroke {
-    const N_SAMPLES: i32 = 4;
-    let mut points: Vec<Point> = vec![start.into()];
-    let (sx, sy) = start;
-    let (c1x, c1y) = control1;
-    let (c2x, c2y) = control2;
-    let (ex, ey) = end;
-    for i in 1..=N_SAMPLES {
-        let t = i as f64 / (N_SAMPLES + 1) as f64;
-        let s = 1.0 - t;
-        let x = s * s * s * sx + 3.0 * s * s * t * c1x + 3.0 * s * t * t * c2x + t * t * t * ex;
-        let y = s * s * s * sy + 3.0 * s * s * t * c1y + 3.0 * s * t * t * c2y + t * t * t * ey;
-        points.push((x, y).into());
-    }
-    points.push(end.into());
-    Stroke(points)
-}
-
 fn bend_stroke(start: (f64, f64), mid: (f64, f64), end: (f64, f64)) -> Stroke {
     Stroke(vec![start.into(), mid.into(), end.into()])
 }
@@ -160,7 +133,7 @@ fn slash_stroke(
     end: (f64, f64),
 ) -> Stroke {
     let Stroke(points0) = line_stroke(start, mid);
-    let Stroke(points1) = quadratic_bezier_stroke(mid, control, end);
+    let Stroke(points1) = quadratic_bezier_stroke(mid.into(), control.into(), end.into());
     Stroke(
         points0
             .into_iter()
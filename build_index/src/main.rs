@@ -1,51 +1,218 @@
 mod dump_reader;
 mod glyph_name;
+mod inkml_export;
 mod kage;
+mod stroke_type_check;
+mod strokes_dump;
 
+use std::borrow::Cow;
+use std::fs::File;
 use std::io::{self, Write};
 use std::os::unix::fs::MetadataExt;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use gwtegaki_model::{FEATURE_COLSIZE, MODEL_VERSION, strokes_to_feature_array};
+use gwtegaki_model::{
+    DEFAULT_MODEL_VERSION, ExtractorParams, FeatureExtractor, ModelParams, Quantization,
+    QuantizedFeature, QuantizedFormat, SegmentModel, get_extractor,
+};
 use indicatif::ProgressBar;
 use itertools::Itertools;
 
 use crate::dump_reader::Dump;
 use crate::glyph_name::is_target_glyph_name;
+use crate::inkml_export::export_inkml;
 use crate::kage::{BuhinRecurser, kage_is_alias};
+use crate::stroke_type_check::check_stroke_types;
+use crate::strokes_dump::dump_strokes;
 
 fn main() {
     let args: Vec<String> = std::env::args().collect();
-    if args.len() != 2 {
-        eprintln!("Usage: {} <dumpfilepath>", args[0]);
-        std::process::exit(1);
+    let mut model_version = None;
+    let mut paramsfilepath = None;
+    let mut quantize = None;
+    let mut check_types = false;
+    let mut dump_strokes_only = false;
+    let mut export_inkml_only = false;
+    let mut positionals = vec![];
+    let mut rest = args[1..].iter();
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "--model" => model_version = Some(rest.next().unwrap_or_else(|| usage(&args[0]))),
+            "--params" => paramsfilepath = Some(rest.next().unwrap_or_else(|| usage(&args[0]))),
+            "--quantize" => {
+                quantize = match rest.next().map(|s| s.as_str()) {
+                    Some("u8") => Some(QuantizedFormat::U8),
+                    Some("f16") => Some(QuantizedFormat::F16),
+                    _ => usage(&args[0]),
+                }
+            }
+            "--check-stroke-types" => check_types = true,
+            "--dump-strokes" => dump_strokes_only = true,
+            "--export-inkml" => export_inkml_only = true,
+            _ => positionals.push(arg),
+        }
     }
-    let dumpfilepath = PathBuf::from(&args[1]);
+    let [dumpfilepath] = positionals[..] else {
+        usage(&args[0])
+    };
+    let dumpfilepath = PathBuf::from(dumpfilepath);
     if !dumpfilepath.exists() {
         eprintln!("Error: file not found: {}", dumpfilepath.display());
         std::process::exit(1);
     }
 
-    if let Err(err) = run(dumpfilepath) {
+    if check_types {
+        if let Err(err) = check_stroke_types(&dumpfilepath) {
+            eprintln!("Application error: {}", err);
+            std::process::exit(1);
+        }
+        return;
+    }
+    if dump_strokes_only {
+        if let Err(err) = dump_strokes(&dumpfilepath) {
+            eprintln!("Application error: {}", err);
+            std::process::exit(1);
+        }
+        return;
+    }
+    if export_inkml_only {
+        if let Err(err) = export_inkml(&dumpfilepath) {
+            eprintln!("Application error: {}", err);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    let custom_model;
+    let extractor: &dyn FeatureExtractor = if let Some(paramsfilepath) = paramsfilepath {
+        // a model with custom parameters is recorded under the name given by --model
+        let params = match read_params(Path::new(paramsfilepath)) {
+            Ok(params) => params,
+            Err(err) => {
+                eprintln!("Error: failed to read params file: {}", err);
+                std::process::exit(1);
+            }
+        };
+        let model_version = model_version.map_or("custom", |v| v.as_str());
+        custom_model = SegmentModel::new(Cow::Owned(model_version.to_string()), params);
+        &custom_model
+    } else {
+        let model_version = model_version.map_or(DEFAULT_MODEL_VERSION, |v| v.as_str());
+        let Some(extractor) = get_extractor(model_version) else {
+            eprintln!("Error: unknown model version: {}", model_version);
+            std::process::exit(1);
+        };
+        extractor
+    };
+
+    if let Err(err) = run(dumpfilepath, extractor, quantize) {
         eprintln!("Application error: {}", err);
         std::process::exit(1);
     }
 }
 
-fn run(dumpfilepath: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+fn usage(program: &str) -> ! {
+    eprintln!(
+        "Usage: {} [--model <version>] [--params <paramsfilepath>] [--quantize u8|f16] <dumpfilepath>",
+        program
+    );
+    eprintln!("       {} --check-stroke-types <dumpfilepath>", program);
+    eprintln!("       {} --dump-strokes <dumpfilepath>", program);
+    eprintln!("       {} --export-inkml <dumpfilepath>", program);
+    std::process::exit(1);
+}
+
+fn read_params(paramsfilepath: &Path) -> Result<ModelParams, Box<dyn std::error::Error>> {
+    let file = File::open(paramsfilepath)?;
+    let params: ModelParams = serde_json::from_reader(io::BufReader::new(file))?;
+    params.check()?;
+    Ok(params)
+}
+
+fn run(
+    dumpfilepath: PathBuf,
+    extractor: &dyn FeatureExtractor,
+    quantize: Option<QuantizedFormat>,
+) -> Result<(), Box<dyn std::error::Error>> {
     let dump = Dump::read_from_file(&dumpfilepath)?;
 
     let mut writer = FeatureWriter::new();
-    {
-        let metadata = dumpfilepath.metadata()?;
+    let timestamp = dumpfilepath.metadata()?.mtime() * 1000;
+    let Some(format) = quantize else {
         writer.write_metadata(
-            metadata.mtime() * 1000,
-            MODEL_VERSION,
-            FEATURE_COLSIZE,
+            timestamp,
+            extractor.model_version(),
+            extractor.feature_colsize(),
             dump.len(),
+            extractor.extractor_params(),
+            None,
         )?;
+        for_each_feature(&dump, extractor, |name, feature| {
+            writer.write_feature(name, &feature)
+        })?;
+        writer.flush()?;
+        return Ok(());
+    };
+
+    // the mapping is fitted to the range of all the features, so they are kept until the end
+    let mut features = Vec::new();
+    let mut min = f64::INFINITY;
+    let mut max = f64::NEG_INFINITY;
+    for_each_feature(&dump, extractor, |name, feature| {
+        for &x in &feature {
+            min = min.min(x);
+            max = max.max(x);
+        }
+        // f32 is still much finer than the quantized codes
+        features.push((
+            name.to_string(),
+            feature.iter().map(|&x| x as f32).collect_vec(),
+        ));
+        Ok(())
+    })?;
+    let quantization = if features.is_empty() {
+        extractor.quantization()
+    } else {
+        Quantization::fit(min, max)
+    };
+
+    let feature_f64 = |feature: &[f32]| feature.iter().map(|&x| x as f64).collect_vec();
+    let saturated: usize = features
+        .iter()
+        .map(|(_, feature)| quantization.saturated_count(&feature_f64(feature), format))
+        .sum();
+    let format_name = match format {
+        QuantizedFormat::U8 => "u8",
+        QuantizedFormat::F16 => "f16",
+    };
+    eprintln!(
+        "Quantized to {} with scale {} and offset {}; {} values saturated",
+        format_name, quantization.scale, quantization.offset, saturated
+    );
+
+    writer.write_metadata(
+        timestamp,
+        extractor.model_version(),
+        extractor.feature_colsize(),
+        features.len(),
+        extractor.extractor_params(),
+        Some((format, quantization, saturated)),
+    )?;
+    for (name, feature) in &features {
+        let feature = quantization.quantize(&feature_f64(feature), format);
+        writer.write_quantized_feature(name, &feature)?;
     }
+    writer.flush()?;
 
+    Ok(())
+}
+
+/// Calls `f` with the name and feature of each target glyph in `dump`.
+fn for_each_feature(
+    dump: &Dump,
+    extractor: &dyn FeatureExtractor,
+    mut f: impl FnMut(&str, Vec<f64>) -> Result<(), Box<dyn std::error::Error>>,
+) -> Result<(), Box<dyn std::error::Error>> {
     let pb = ProgressBar::new(dump.len().try_into().unwrap());
 
     for (name, data) in dump.iter() {
@@ -58,14 +225,19 @@ fn run(dumpfilepath: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
             continue;
         }
         let mut recurser = BuhinRecurser::new();
-        let strokes = recurser.kage_data_to_strokes(data, &dump);
+        let strokes = recurser.kage_data_to_strokes(data, dump);
         if strokes.is_empty() {
             continue;
         }
-        let feature = strokes_to_feature_array(&strokes);
-        writer.write_feature(name, &feature)?;
+        let feature = match extractor.try_strokes_to_feature_array(&strokes) {
+            Ok(feature) => feature,
+            Err(err) => {
+                pb.println(format!("Warning: skipping {}: {}", name, err));
+                continue;
+            }
+        };
+        f(name, feature)?;
     }
-    writer.flush()?;
     pb.finish();
 
     Ok(())
@@ -87,11 +259,33 @@ impl FeatureWriter {
         v: &str,
         dimen: usize,
         len_hint: usize,
+        params: Option<ExtractorParams>,
+        quantization: Option<(QuantizedFormat, Quantization, usize)>,
     ) -> Result<(), Box<dyn std::error::Error>> {
+        // the rest of the metadata is a JSON object to be merged into metadata.json
+        let mut extra = serde_json::Map::new();
+        if let Some(params) = params {
+            extra.insert("params".to_string(), serde_json::to_value(params)?);
+        }
+        if let Some((format, Quantization { scale, offset }, saturated)) = quantization {
+            extra.insert(
+                "quantization".to_string(),
+                serde_json::json!({
+                    "format": format,
+                    "scale": scale,
+                    "offset": offset,
+                    "saturated": saturated,
+                }),
+            );
+        }
         writeln!(
             &mut self.inner,
-            "{} {} {} {}",
-            timestamp, v, dimen, len_hint
+            "{} {} {} {} {}",
+            timestamp,
+            v,
+            dimen,
+            len_hint,
+            serde_json::Value::Object(extra)
         )?;
         Ok(())
     }
@@ -105,6 +299,19 @@ impl FeatureWriter {
         Ok(())
     }
 
+    fn write_quantized_feature(
+        &mut self,
+        name: &str,
+        feature: &QuantizedFeature,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let codes = match feature {
+            QuantizedFeature::U8(codes) => codes.iter().join(","),
+            QuantizedFeature::F16(codes) => codes.iter().join(","),
+        };
+        writeln!(&mut self.inner, "{} {}", name, codes)?;
+        Ok(())
+    }
+
     fn flush(&mut self) -> Result<(), Box<dyn std::error::Error>> {
         self.inner.flush()?;
         Ok(())
@@ -0,0 +1,86 @@
+use std::path::Path;
+
+use gwtegaki_model::StrokeType;
+use indicatif::ProgressBar;
+
+use crate::dump_reader::Dump;
+use crate::glyph_name::is_target_glyph_name;
+use crate::kage::{BuhinRecurser, kage_is_alias};
+
+const KAGE_STROKE_TYPES: [i32; 6] = [1, 2, 3, 4, 6, 7];
+
+/// Stroke types a stroke of the given KAGE type can reasonably be classified as.
+fn expected_types(kage_stroke_type: i32) -> &'static [StrokeType] {
+    use StrokeType::*;
+    match kage_stroke_type {
+        // straight line
+        1 => &[Horizontal, Vertical, LeftFalling, RightFalling, Dot],
+        // curve: sweeps and dots
+        2 => &[LeftFalling, RightFalling, Dot],
+        // bent line and the bent line sweeping right
+        3 | 4 => &[Bend, Hook],
+        // compound curve
+        6 => &[LeftFalling, RightFalling, Hook, Bend],
+        // vertical line sweeping left
+        7 => &[Vertical, LeftFalling, Hook, Bend],
+        _ => &[],
+    }
+}
+
+/// Classifies every stroke of the target glyphs and prints the confusion table of the KAGE
+/// stroke types against the classified types, with the rate of expected classifications.
+pub fn check_stroke_types(dumpfilepath: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let dump = Dump::read_from_file(dumpfilepath)?;
+
+    let mut counts = [[0usize; StrokeType::ALL.len()]; KAGE_STROKE_TYPES.len()];
+    let pb = ProgressBar::new(dump.len().try_into().unwrap());
+    for (name, data) in dump.iter() {
+        pb.inc(1);
+
+        if kage_is_alias(data) || !is_target_glyph_name(name) {
+            continue;
+        }
+        let mut recurser = BuhinRecurser::new();
+        for (kage_stroke_type, stroke) in recurser.kage_data_to_typed_strokes(data, &dump) {
+            let Some(row) = KAGE_STROKE_TYPES
+                .iter()
+                .position(|&t| t == kage_stroke_type)
+            else {
+                continue;
+            };
+            let column = StrokeType::ALL
+                .iter()
+                .position(|&t| t == stroke.stroke_type())
+                .unwrap();
+            counts[row][column] += 1;
+        }
+    }
+    pb.finish();
+
+    print!("kage");
+    for stroke_type in StrokeType::ALL {
+        print!("\t{:?}", stroke_type);
+    }
+    println!("\texpected");
+    for (kage_stroke_type, row) in KAGE_STROKE_TYPES.iter().zip(&counts) {
+        let expected = expected_types(*kage_stroke_type);
+        let total: usize = row.iter().sum();
+        let matched: usize = StrokeType::ALL
+            .iter()
+            .zip(row)
+            .filter(|(t, _)| expected.contains(t))
+            .map(|(_, count)| count)
+            .sum();
+        print!("{}", kage_stroke_type);
+        for count in row {
+            print!("\t{}", count);
+        }
+        if total > 0 {
+            println!("\t{:.1}%", matched as f64 / total as f64 * 100.0);
+        } else {
+            println!("\t-");
+        }
+    }
+
+    Ok(())
+}
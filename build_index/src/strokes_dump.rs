@@ -0,0 +1,40 @@
+use std::io::{self, Write};
+use std::path::Path;
+
+use gwtegaki_model::{CanvasSpec, Drawing};
+use indicatif::ProgressBar;
+
+use crate::dump_reader::Dump;
+use crate::glyph_name::is_target_glyph_name;
+use crate::kage::{BuhinRecurser, kage_is_alias};
+
+/// Writes the strokes of every target glyph as JSON Lines, one [`Drawing`] per glyph with its
+/// name in the `name` metadata.
+pub fn dump_strokes(dumpfilepath: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let dump = Dump::read_from_file(dumpfilepath)?;
+
+    let mut writer = io::BufWriter::new(io::stdout());
+    let pb = ProgressBar::new(dump.len().try_into().unwrap());
+    for (name, data) in dump.iter() {
+        pb.inc(1);
+
+        if kage_is_alias(data) || !is_target_glyph_name(name) {
+            continue;
+        }
+        let mut recurser = BuhinRecurser::new();
+        let strokes = recurser.kage_data_to_strokes(data, &dump);
+        if strokes.is_empty() {
+            continue;
+        }
+        let mut drawing = Drawing::new(CanvasSpec::GLYPHWIKI, strokes);
+        drawing
+            .metadata
+            .insert("name".to_string(), name.to_string());
+        serde_json::to_writer(&mut writer, &drawing)?;
+        writeln!(&mut writer)?;
+    }
+    writer.flush()?;
+    pb.finish();
+
+    Ok(())
+}
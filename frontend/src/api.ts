@@ -4,6 +4,15 @@ export interface WarmupResponse {
   dumpTime: number;
   numItems: number;
   v: string;
+  /**
+   * Present if the index was built from quantized features; queries must then be quantized
+   * the same way.
+   */
+  quantization?: {
+    format: "u8" | "f16";
+    scale: number;
+    offset: number;
+  };
 }
 
 export async function callApiWarmup(): Promise<WarmupResponse> {
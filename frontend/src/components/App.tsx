@@ -119,14 +119,28 @@ const apiWarmup = (() => {
 
 const gwtegakiModelPromise = import("gwtegaki-model");
 async function searchByStrokes(strokes: Stroke[]) {
-  const { strokes_to_feature_array, modelVersion } = await gwtegakiModelPromise;
-  const feature = strokes_to_feature_array(strokes).map((x) => Math.fround(x));
-  const query = feature.join(" ");
+  const {
+    strokes_to_feature_array,
+    quantize_feature,
+    modelVersion,
+    modelVersions,
+  } = await gwtegakiModelPromise;
 
   await apiWarmup().catch(() => {
     /* ignore */
   });
-  return await callApiSearch(modelVersion, query);
+  const meta = metaAtom.get();
+  // query with the model of the index being served, so that the page keeps working while
+  // indexes of another model version roll out
+  const v = meta && modelVersions.includes(meta.v) ? meta.v : modelVersion;
+  const feature = strokes_to_feature_array(strokes, v).map((x) =>
+    Math.fround(x)
+  );
+  const quantization = meta?.quantization;
+  const query = (
+    quantization ? quantize_feature(feature, quantization) : feature
+  ).join(" ");
+  return await callApiSearch(v, query);
 }
 
 const emptyResultLoadable = new Loadable<undefined>(Promise.resolve(undefined));
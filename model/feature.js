@@ -2,34 +2,183 @@
 
 import {
   model_version,
+  model_versions,
   feature_colsize,
-  strokes_flattened_to_feature_array,
+  fuse_search_results as fuse_search_results_flattened,
+  FeatureModel,
+  Quantization,
 } from "./pkg/gwtegaki_model.js";
 
 const modelVersion = model_version();
 
+const modelVersions = model_versions();
+
 const FEATURE_COLSIZE = feature_colsize();
 
 /** @typedef {[number, number]} Point */
 /** @typedef {Point[]} Stroke */
 
-/** @param {Stroke[]} strokes */
-function strokes_to_feature_array(strokes) {
-  const strokes_flattened_length =
-    1 + strokes.reduce((acc, stroke) => acc + 1 + stroke.length * 2, 0);
-  const strokes_flattened = new Int32Array(strokes_flattened_length);
-  let idx = 0;
-  strokes_flattened[idx++] = strokes.length;
-  for (const stroke of strokes) {
-    strokes_flattened[idx++] = stroke.length;
-    for (const [x, y] of stroke) {
-      strokes_flattened[idx++] = x;
-      strokes_flattened[idx++] = y;
-    }
+// types of the typed wasm API, generated along with the bindings
+/** @typedef {import("./pkg/gwtegaki_model.js").CanvasInput} CanvasInput */
+/** @typedef {import("./pkg/gwtegaki_model.js").ModelError} ModelError */
+/** @typedef {import("./pkg/gwtegaki_model.js").PointInput} PointInput */
+/** @typedef {import("./pkg/gwtegaki_model.js").StrokesInput} StrokesInput */
+/** @typedef {import("./pkg/gwtegaki_model.js").Explanation} Explanation */
+/** @typedef {import("./pkg/gwtegaki_model.js").CellLabel} CellLabel */
+/** @typedef {import("./pkg/gwtegaki_model.js").CellDifference} CellDifference */
+/** @typedef {import("./pkg/gwtegaki_model.js").CellDifferences} CellDifferences */
+/** @typedef {import("./pkg/gwtegaki_model.js").SvgDrawing} SvgDrawing */
+/** @typedef {import("./pkg/gwtegaki_model.js").FeatureArrays} FeatureArrays */
+/** @typedef {import("./pkg/gwtegaki_model.js").StrokesOutput} StrokesOutput */
+
+/** @type {Map<string, FeatureModel>} */
+const featureModels = new Map();
+
+/** @param {string} v */
+function getFeatureModel(v) {
+  let model = featureModels.get(v);
+  if (!model) {
+    model = new FeatureModel(v);
+    featureModels.set(v, model);
   }
-  const feature_array_f64 =
-    strokes_flattened_to_feature_array(strokes_flattened);
+  return model;
+}
+
+/**
+ * Points may also be given as `{ x, y, pressure?, time? }` with the pen data of the pointer
+ * events, which the preprocessing of newer models makes use of. Leave out the pressure of
+ * mice, which always report 0.5.
+ *
+ * Throws an `Error` named `ModelError` on invalid strokes.
+ *
+ * @param {PointInput[][]} strokes
+ * @param {string=} v
+ * @returns {number[]}
+ */
+function strokes_to_feature_array(strokes, v) {
+  const feature_array_f64 = getFeatureModel(
+    v ?? modelVersion
+  ).strokes_to_feature_array(strokes);
   return Array.from(feature_array_f64);
 }
 
-export { strokes_to_feature_array, FEATURE_COLSIZE, modelVersion };
+/**
+ * The feature of the strokes with the elements it was rendered from, for debugging.
+ * `undefined` if the model cannot explain its features.
+ *
+ * @param {PointInput[][]} strokes
+ * @param {number} top_cells number of cells listed for each element
+ * @param {string=} v
+ * @returns {Explanation | undefined}
+ */
+function explain_feature_array(strokes, top_cells, v) {
+  return getFeatureModel(v ?? modelVersion).explain_feature_array(
+    strokes,
+    top_cells
+  );
+}
+
+/**
+ * The `top` cells accounting for most of the distance between two features, largest first.
+ *
+ * @param {number[]} a
+ * @param {number[]} b
+ * @param {number} top
+ * @param {string=} v
+ * @returns {CellDifferences}
+ */
+function compare_feature_arrays(a, b, top, v) {
+  return getFeatureModel(v ?? modelVersion).compare_feature_arrays(
+    Float64Array.from(a),
+    Float64Array.from(b),
+    top
+  );
+}
+
+/**
+ * @param {string} v
+ * @returns {number}
+ */
+function feature_colsize_of(v) {
+  return getFeatureModel(v).colsize;
+}
+
+/**
+ * Features of `n` perturbed copies of the strokes, deterministic for a given `seed`.
+ *
+ * Throws an `Error` named `ModelError` on invalid strokes.
+ *
+ * @param {PointInput[][]} strokes
+ * @param {number} n
+ * @param {number} seed
+ * @param {string=} v
+ * @returns {FeatureArrays}
+ */
+function augmented_feature_arrays(strokes, n, seed, v) {
+  return getFeatureModel(v ?? modelVersion).augmented_feature_arrays(
+    strokes,
+    n,
+    seed
+  );
+}
+
+/**
+ * Quantizes a feature with the mapping recorded in the metadata of a quantized index, to query
+ * it. Returns the u8 codes, or the bit patterns of the f16 values.
+ *
+ * @param {number[]} feature
+ * @param {{ format: "u8" | "f16", scale: number, offset: number }} quantization
+ * @returns {number[]}
+ */
+function quantize_feature(feature, { format, scale, offset }) {
+  const mapping = new Quantization(scale, offset);
+  const values = Float64Array.from(feature);
+  const codes =
+    format === "f16" ? mapping.quantize_f16(values) : mapping.quantize_u8(values);
+  mapping.free();
+  return Array.from(codes);
+}
+
+/** @typedef {{ name: string, distance: number }} SearchResult */
+
+/**
+ * Merges the kNN results of several queries, best first.
+ *
+ * @param {SearchResult[][]} lists
+ * @param {"min_distance" | "reciprocal_rank"} method
+ * @returns {{ name: string, score: number }[]}
+ */
+function fuse_search_results(lists, method) {
+  const results = lists.flat();
+  const fused = fuse_search_results_flattened(
+    results.map(({ name }) => name),
+    Float64Array.from(results, ({ distance }) => distance),
+    Uint32Array.from(lists, (list) => list.length),
+    method
+  );
+  const names = fused.names;
+  const scores = fused.scores;
+  fused.free();
+  return names.map((name, i) => ({ name, score: scores[i] }));
+}
+
+export {
+  FeatureAccumulator,
+  FeatureModel,
+  Quantization,
+  cleanup_strokes,
+  drawing_from_svg,
+} from "./pkg/gwtegaki_model.js";
+
+export {
+  strokes_to_feature_array,
+  explain_feature_array,
+  compare_feature_arrays,
+  feature_colsize_of,
+  augmented_feature_arrays,
+  quantize_feature,
+  fuse_search_results,
+  FEATURE_COLSIZE,
+  modelVersion,
+  modelVersions,
+};
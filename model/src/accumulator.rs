@@ -0,0 +1,247 @@
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    canvas::CanvasSpec,
+    error::ModelError,
+    extractor::FeatureExtractor,
+    stroke::{Point, Stroke, check_stroke},
+};
+
+/// Keeps the feature of a drawing up to date while strokes are added and removed, for
+/// search-as-you-draw.
+///
+/// If the extractor provides per-stroke features (see
+/// [`FeatureExtractor::stroke_feature_array`]), only the changed stroke is processed; otherwise
+/// the feature is recomputed from all the strokes on each change. Of the registered models, only
+/// v2 and v3 are additive: from v4 on, the normalization (and later the global descriptors, the
+/// stroke relations and the segmentation) depend on all the strokes together, so the
+/// accumulator gives the same results as extracting the feature from scratch but saves no work.
+#[wasm_bindgen]
+pub struct FeatureAccumulator {
+    extractor: &'static dyn FeatureExtractor,
+    canvas: CanvasSpec,
+    /// Strokes in the [`CanvasSpec::GLYPHWIKI`] frame.
+    strokes: Vec<Stroke>,
+    /// Features of each stroke, if the extractor is additive.
+    stroke_features: Option<Vec<Vec<f64>>>,
+    feature: Vec<f64>,
+}
+
+impl FeatureAccumulator {
+    /// Creates an accumulator for strokes drawn on `canvas`.
+    pub fn new(
+        extractor: &'static dyn FeatureExtractor,
+        canvas: CanvasSpec,
+    ) -> Result<Self, ModelError> {
+        canvas.check()?;
+        let feature = vec![0.0; extractor.feature_colsize()];
+        Ok(FeatureAccumulator {
+            extractor,
+            canvas,
+            strokes: Vec::new(),
+            stroke_features: Some(Vec::new()),
+            feature,
+        })
+    }
+
+    pub fn add_stroke(&mut self, stroke: &Stroke) -> Result<(), ModelError> {
+        check_stroke(stroke, self.strokes.len())?;
+        let stroke = Stroke(
+            stroke
+                .0
+                .iter()
+                .map(|p| self.canvas.to_glyphwiki(p))
+                .collect(),
+        );
+
+        match self
+            .stroke_features
+            .as_mut()
+            .zip(self.extractor.stroke_feature_array(&stroke))
+        {
+            Some((stroke_features, stroke_feature)) => {
+                for (a, b) in self.feature.iter_mut().zip(&stroke_feature) {
+                    *a += b;
+                }
+                stroke_features.push(stroke_feature);
+                self.strokes.push(stroke);
+            }
+            None => {
+                self.stroke_features = None;
+                self.strokes.push(stroke);
+                self.recompute();
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes the last stroke. Returns `false` if there is no stroke.
+    pub fn remove_last_stroke(&mut self) -> bool {
+        if self.strokes.pop().is_none() {
+            return false;
+        }
+        match &mut self.stroke_features {
+            Some(stroke_features) => {
+                // summing up again instead of subtracting so that no error accumulates
+                stroke_features.pop();
+                self.feature.fill(0.0);
+                for stroke_feature in stroke_features.iter() {
+                    for (a, b) in self.feature.iter_mut().zip(stroke_feature) {
+                        *a += b;
+                    }
+                }
+            }
+            None => self.recompute(),
+        }
+        true
+    }
+
+    pub fn current_feature(&self) -> &[f64] {
+        &self.feature
+    }
+
+    /// The strokes added so far, in the [`CanvasSpec::GLYPHWIKI`] frame.
+    pub fn strokes(&self) -> &[Stroke] {
+        &self.strokes
+    }
+
+    fn recompute(&mut self) {
+        self.feature = self.extractor.strokes_to_feature_array(&self.strokes);
+    }
+}
+
+#[wasm_bindgen]
+impl FeatureAccumulator {
+    #[wasm_bindgen(getter)]
+    pub fn version(&self) -> String {
+        self.extractor.model_version().to_string()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn stroke_count(&self) -> usize {
+        self.strokes.len()
+    }
+
+    /// Adds a stroke given as interleaved (x, y) coordinates.
+    pub fn add_stroke_flattened(&mut self, points_flattened: &[f64]) -> Result<(), JsError> {
+        if !points_flattened.len().is_multiple_of(2) {
+            return Err(ModelError::OddCoordinateCount {
+                len: points_flattened.len(),
+            }
+            .into());
+        }
+        let points = points_flattened
+            .chunks_exact(2)
+            .map(|xy| Point { x: xy[0], y: xy[1] })
+            .collect();
+        Ok(self.add_stroke(&Stroke(points))?)
+    }
+
+    #[wasm_bindgen(js_name = remove_last_stroke)]
+    pub fn undo(&mut self) -> bool {
+        self.remove_last_stroke()
+    }
+
+    pub fn clear(&mut self) {
+        self.strokes.clear();
+        if let Some(stroke_features) = &mut self.stroke_features {
+            stroke_features.clear();
+        }
+        self.recompute();
+    }
+
+    #[wasm_bindgen(js_name = current_feature)]
+    pub fn current_feature_array(&self) -> Box<[f64]> {
+        self.feature.clone().into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extractor::get_extractor;
+
+    fn strokes() -> Vec<Stroke> {
+        [
+            vec![(30.0, 40.0), (170.0, 40.0)],
+            vec![(100.0, 15.5), (100.0, 120.0), (60.0, 175.25)],
+            vec![(50.0, 90.0), (150.0, 90.0), (140.0, 160.0)],
+        ]
+        .into_iter()
+        .map(|points| Stroke(points.into_iter().map(Point::from).collect()))
+        .collect()
+    }
+
+    fn assert_close(a: &[f64], b: &[f64]) {
+        assert_eq!(a.len(), b.len());
+        for (i, (x, y)) in a.iter().zip(b).enumerate() {
+            assert!((x - y).abs() < 1e-12, "element {}: {} != {}", i, x, y);
+        }
+    }
+
+    #[test]
+    fn matches_feature_from_scratch() {
+        let strokes = strokes();
+        // v2 and v3 are updated per stroke, v4 recomputes
+        for version in ["2", "3", "4"] {
+            let extractor = get_extractor(version).unwrap();
+            assert_eq!(
+                extractor.stroke_feature_array(&strokes[0]).is_some(),
+                version != "4"
+            );
+            let from_scratch = |n: usize| extractor.strokes_to_feature_array(&strokes[..n]);
+            let mut accumulator =
+                FeatureAccumulator::new(extractor, CanvasSpec::GLYPHWIKI).unwrap();
+            assert_close(accumulator.current_feature(), &from_scratch(0));
+            for n in 1..=strokes.len() {
+                accumulator.add_stroke(&strokes[n - 1]).unwrap();
+                assert_close(accumulator.current_feature(), &from_scratch(n));
+            }
+            assert!(accumulator.remove_last_stroke());
+            assert_close(accumulator.current_feature(), &from_scratch(2));
+            accumulator.add_stroke(&strokes[2]).unwrap();
+            assert_close(accumulator.current_feature(), &from_scratch(3));
+
+            accumulator.clear();
+            assert_eq!(accumulator.stroke_count(), 0);
+            assert_close(accumulator.current_feature(), &from_scratch(0));
+            assert!(!accumulator.remove_last_stroke());
+            accumulator.add_stroke(&strokes[0]).unwrap();
+            assert_close(accumulator.current_feature(), &from_scratch(1));
+        }
+    }
+
+    #[test]
+    fn takes_fractional_coordinates_on_a_canvas() {
+        let strokes = strokes();
+        let extractor = get_extractor("3").unwrap();
+        let canvas = CanvasSpec {
+            width: 400.0,
+            height: 400.0,
+            origin_x: 0.0,
+            origin_y: 0.0,
+        };
+        let mut accumulator = FeatureAccumulator::new(extractor, canvas).unwrap();
+        for stroke in &strokes {
+            let flattened: Vec<f64> = stroke
+                .0
+                .iter()
+                .flat_map(|p| [2.0 * p.x, 2.0 * p.y])
+                .collect();
+            accumulator.add_stroke_flattened(&flattened).unwrap();
+        }
+        assert_close(
+            accumulator.current_feature(),
+            &extractor.strokes_to_feature_array(&strokes),
+        );
+        assert!(
+            accumulator
+                .add_stroke(&Stroke(vec![Point {
+                    x: f64::NAN,
+                    y: 0.0
+                }]))
+                .is_err()
+        );
+        assert_eq!(accumulator.stroke_count(), strokes.len());
+    }
+}
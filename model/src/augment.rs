@@ -0,0 +1,154 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    canvas::CanvasSpec,
+    stroke::{Point, Stroke},
+};
+
+/// Ranges of the random perturbations applied to a query to make augmented copies of it.
+///
+/// Each copy is scaled, slanted, rotated and shifted around the center of the
+/// [`CanvasSpec::GLYPHWIKI`] frame, then every stroke is shifted by its own jitter. Each
+/// parameter is drawn uniformly from `[-max, max]`. Lengths are in units of the 200x200 frame.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AugmentParams {
+    /// Largest relative change of the size, applied to both axes alike.
+    pub max_scale: f64,
+    /// Largest shift of the whole drawing along each axis.
+    pub max_shift: f64,
+    /// Largest rotation, in radians.
+    pub max_rotation: f64,
+    /// Largest horizontal shear (dx/dy).
+    pub max_slant: f64,
+    /// Largest shift of a single stroke along each axis.
+    pub jitter: f64,
+}
+
+impl AugmentParams {
+    pub const DEFAULT: AugmentParams = AugmentParams {
+        max_scale: 0.1,
+        max_shift: 10.0,
+        max_rotation: 0.08,
+        max_slant: 0.15,
+        jitter: 4.0,
+    };
+
+    /// Makes `n` perturbed copies of `strokes`. The copies only depend on the arguments, so
+    /// the same `seed` always gives the same copies.
+    pub fn augment(&self, strokes: &[Stroke], n: usize, seed: u64) -> Vec<Vec<Stroke>> {
+        let mut rng = SplitMix64(seed);
+        (0..n).map(|_| self.perturb(strokes, &mut rng)).collect()
+    }
+
+    fn perturb(&self, strokes: &[Stroke], rng: &mut SplitMix64) -> Vec<Stroke> {
+        let frame = CanvasSpec::GLYPHWIKI;
+        let (cx, cy) = (
+            frame.origin_x + frame.width / 2.0,
+            frame.origin_y + frame.height / 2.0,
+        );
+        let scale = 1.0 + rng.uniform(self.max_scale);
+        let slant = rng.uniform(self.max_slant);
+        let (sin, cos) = rng.uniform(self.max_rotation).sin_cos();
+        let (shift_x, shift_y) = (rng.uniform(self.max_shift), rng.uniform(self.max_shift));
+
+        strokes
+            .iter()
+            .map(|stroke| {
+                let (jitter_x, jitter_y) = (rng.uniform(self.jitter), rng.uniform(self.jitter));
+                let points = stroke
+                    .0
+                    .iter()
+                    .map(|p| {
+                        let (x, y) = ((p.x - cx) * scale, (p.y - cy) * scale);
+                        let x = x + slant * y;
+                        let (x, y) = (x * cos - y * sin, x * sin + y * cos);
+                        Point {
+                            x: x + cx + shift_x + jitter_x,
+                            y: y + cy + shift_y + jitter_y,
+                        }
+                    })
+                    .collect();
+                Stroke(points)
+            })
+            .collect()
+    }
+}
+
+impl Default for AugmentParams {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// A small PRNG that gives the same sequence on every platform and in every release, unlike
+/// the generators of the `rand` crate.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `[-max, max]`.
+    fn uniform(&mut self, max: f64) -> f64 {
+        let unit = (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64;
+        (2.0 * unit - 1.0) * max
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extractor::get_extractor;
+
+    fn glyph() -> Vec<Stroke> {
+        [
+            [(20.0, 100.0), (180.0, 100.0)],
+            [(100.0, 20.0), (100.0, 180.0)],
+        ]
+        .iter()
+        .map(|points| Stroke(points.iter().map(|&(x, y)| Point { x, y }).collect()))
+        .collect()
+    }
+
+    #[test]
+    fn copies_depend_only_on_the_seed() {
+        let extractor = get_extractor("6").unwrap();
+        let augment = |seed| {
+            extractor.try_augmented_feature_arrays(&glyph(), &AugmentParams::DEFAULT, 3, seed)
+        };
+        let features = augment(42).unwrap();
+        assert_eq!(features.len(), 3);
+        assert_eq!(features, augment(42).unwrap());
+        assert_ne!(features, augment(43).unwrap());
+        // the copies differ from each other and from the query
+        let query = extractor.strokes_to_feature_array(&glyph());
+        assert_ne!(features[0], features[1]);
+        assert!(features.iter().all(|feature| *feature != query));
+    }
+
+    #[test]
+    fn no_perturbation_gives_the_query() {
+        let none = AugmentParams {
+            max_scale: 0.0,
+            max_shift: 0.0,
+            max_rotation: 0.0,
+            max_slant: 0.0,
+            jitter: 0.0,
+        };
+        let copies = none.augment(&glyph(), 2, 7);
+        for copy in &copies {
+            for (stroke, original) in copy.iter().zip(&glyph()) {
+                for (p, q) in stroke.0.iter().zip(&original.0) {
+                    assert_eq!((p.x, p.y), (q.x, q.y));
+                }
+            }
+        }
+    }
+}
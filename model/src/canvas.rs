@@ -0,0 +1,107 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::ModelError,
+    stroke::{PenPoint, PenStroke, Point, Stroke},
+};
+
+/// The coordinate space input strokes are drawn in.
+///
+/// The models work in GlyphWiki's 200x200 frame, and all of their lengths and thresholds are
+/// relative to it. Strokes on other canvases are fitted into the frame (centred, preserving the
+/// aspect ratio) before feature extraction.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CanvasSpec {
+    pub width: f64,
+    pub height: f64,
+    /// Coordinates of the top-left corner of the canvas.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub origin_x: f64,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub origin_y: f64,
+}
+
+impl CanvasSpec {
+    /// The frame of GlyphWiki's KAGE data, which the models work in.
+    pub const GLYPHWIKI: CanvasSpec = CanvasSpec {
+        width: 200.0,
+        height: 200.0,
+        origin_x: 0.0,
+        origin_y: 0.0,
+    };
+
+    pub fn check(&self) -> Result<(), ModelError> {
+        let is_valid_size = |size: f64| size.is_finite() && size > 0.0;
+        let is_valid_origin = self.origin_x.is_finite() && self.origin_y.is_finite();
+        if is_valid_size(self.width) && is_valid_size(self.height) && is_valid_origin {
+            Ok(())
+        } else {
+            Err(ModelError::InvalidCanvas)
+        }
+    }
+
+    /// Maps a point on this canvas to the corresponding point in [`CanvasSpec::GLYPHWIKI`].
+    pub fn to_glyphwiki(&self, p: &Point) -> Point {
+        let frame = Self::GLYPHWIKI;
+        let scale = (frame.width / self.width).min(frame.height / self.height);
+        let offset_x = (frame.width - self.width * scale) / 2.0;
+        let offset_y = (frame.height - self.height * scale) / 2.0;
+        Point {
+            x: (p.x - self.origin_x) * scale + offset_x + frame.origin_x,
+            y: (p.y - self.origin_y) * scale + offset_y + frame.origin_y,
+        }
+    }
+
+    /// The inverse of [`Self::to_glyphwiki`].
+    pub fn from_glyphwiki(&self, p: &Point) -> Point {
+        let frame = Self::GLYPHWIKI;
+        let scale = (frame.width / self.width).min(frame.height / self.height);
+        let offset_x = (frame.width - self.width * scale) / 2.0;
+        let offset_y = (frame.height - self.height * scale) / 2.0;
+        Point {
+            x: (p.x - frame.origin_x - offset_x) / scale + self.origin_x,
+            y: (p.y - frame.origin_y - offset_y) / scale + self.origin_y,
+        }
+    }
+
+    pub fn strokes_to_glyphwiki(&self, strokes: &[Stroke]) -> Vec<Stroke> {
+        strokes
+            .iter()
+            .map(|stroke| Stroke(stroke.0.iter().map(|p| self.to_glyphwiki(p)).collect()))
+            .collect()
+    }
+
+    /// Same as [`Self::strokes_to_glyphwiki`] for pen strokes, whose pen data is kept as is.
+    pub fn pen_strokes_to_glyphwiki(&self, strokes: &[PenStroke]) -> Vec<PenStroke> {
+        strokes
+            .iter()
+            .map(|stroke| {
+                PenStroke(
+                    stroke
+                        .0
+                        .iter()
+                        .map(|p| {
+                            let Point { x, y } = self.to_glyphwiki(&p.point());
+                            PenPoint { x, y, ..p.clone() }
+                        })
+                        .collect(),
+                )
+            })
+            .collect()
+    }
+
+    pub fn strokes_from_glyphwiki(&self, strokes: &[Stroke]) -> Vec<Stroke> {
+        strokes
+            .iter()
+            .map(|stroke| Stroke(stroke.0.iter().map(|p| self.from_glyphwiki(p)).collect()))
+            .collect()
+    }
+}
+
+impl Default for CanvasSpec {
+    fn default() -> Self {
+        Self::GLYPHWIKI
+    }
+}
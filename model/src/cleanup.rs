@@ -0,0 +1,358 @@
+use std::f64::consts::PI;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    geometry::{distance, turn},
+    stroke::{PenPoint, PenStroke, Stroke},
+};
+
+/// Cleanup of raw pointer input before feature extraction.
+///
+/// The steps run in this order: duplicate-point removal, endpoint hook removal, equidistant
+/// resampling and moving-average smoothing. A step is skipped when its parameter is zero.
+/// Lengths are in units of the 200x200 frame (see [`crate::CanvasSpec`]).
+///
+/// The pen data of [`PenStroke`]s, where present, tells deliberate hooks from noise and makes
+/// the smoothing depend on the pen speed. The checks using it are skipped when their parameter
+/// is zero, as in [`Self::DEFAULT`] and when deserializing older parameters; [`Self::PEN_AWARE`]
+/// enables them.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CleanupParams {
+    /// Points closer than this to the previous kept point are dropped.
+    pub min_point_distance: f64,
+    /// Longest piece at either end of a stroke that can be cut off as a hook.
+    pub max_hook_length: f64,
+    /// Smallest turn (in radians) between a hook and the rest of the stroke.
+    pub min_hook_angle: f64,
+    /// With time data, a hook that took longer than this (in milliseconds) to draw is
+    /// deliberate and kept.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub max_hook_duration: f64,
+    /// With pressure data, a hook pressed harder than this fraction of the mean pressure of
+    /// the rest of the stroke is deliberate and kept. Noise at the ends is drawn while the pen
+    /// lands or lifts, with a light touch.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub max_hook_pressure: f64,
+    /// Distance between consecutive points after resampling.
+    pub resample_step: f64,
+    /// Number of neighbors on each side averaged into a point. The ends are kept in place.
+    pub smoothing_radius: usize,
+    /// With time data, the smoothing radius shrinks in proportion to the pen speed, down to
+    /// none at this speed (in units per millisecond): slow movement jitters the most, and fast
+    /// strokes are left sharp.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub smoothing_speed: f64,
+}
+
+impl CleanupParams {
+    pub const DEFAULT: CleanupParams = CleanupParams {
+        min_point_distance: 1.0,
+        max_hook_length: 8.0,
+        min_hook_angle: PI / 2.0,
+        max_hook_duration: 0.0,
+        max_hook_pressure: 0.0,
+        resample_step: 5.0,
+        smoothing_radius: 1,
+        smoothing_speed: 0.0,
+    };
+
+    /// [`Self::DEFAULT`] with the checks using the pen data.
+    pub const PEN_AWARE: CleanupParams = CleanupParams {
+        max_hook_duration: 40.0,
+        max_hook_pressure: 0.6,
+        smoothing_speed: 2.0,
+        ..Self::DEFAULT
+    };
+
+    pub fn cleanup_stroke(&self, stroke: &Stroke) -> Stroke {
+        Stroke::from(&self.cleanup_pen_stroke(&PenStroke::from(stroke)))
+    }
+
+    /// Same as [`Self::cleanup_stroke`] for a pen stroke. The pen data of resampled points is
+    /// interpolated, and that of smoothed points is kept.
+    pub fn cleanup_pen_stroke(&self, stroke: &PenStroke) -> PenStroke {
+        let mut points = stroke.0.clone();
+        if self.min_point_distance > 0.0 {
+            points = remove_duplicates(&points, self.min_point_distance);
+        }
+        if self.max_hook_length > 0.0 {
+            self.remove_start_hook(&mut points);
+            points.reverse();
+            self.remove_start_hook(&mut points);
+            points.reverse();
+        }
+        if self.resample_step > 0.0 {
+            points = resample(&points, self.resample_step);
+        }
+        if self.smoothing_radius > 0 {
+            points = smooth(&points, self.smoothing_radius, self.smoothing_speed);
+        }
+        PenStroke(points)
+    }
+
+    /// Cuts the sharpest turn within `max_hook_length` of the start, if it turns by more than
+    /// `min_hook_angle` relative to the following part of the stroke and the pen data does not
+    /// show it to be deliberate. Strokes shorter than three times `max_hook_length` are left
+    /// alone, so that short strokes such as dots are not eaten away.
+    fn remove_start_hook(&self, points: &mut Vec<PenPoint>) {
+        let max_length = self.max_hook_length;
+        let arc = arc_lengths(points);
+        if arc.last().is_none_or(|&length| length < 3.0 * max_length) {
+            return;
+        }
+        let mut best: Option<(usize, f64)> = None;
+        for k in 1..points.len() - 1 {
+            if arc[k] > max_length {
+                break;
+            }
+            let far = (k + 1..points.len())
+                .find(|&j| arc[j] >= arc[k] + 2.0 * max_length)
+                .unwrap_or(points.len() - 1);
+            let (p, q, r) = (&points[0], &points[k], &points[far]);
+            let a = (q.y - p.y).atan2(q.x - p.x);
+            let b = (r.y - q.y).atan2(r.x - q.x);
+            let turn = turn(a, b);
+            if turn > self.min_hook_angle && best.is_none_or(|(_, best_turn)| turn > best_turn) {
+                best = Some((k, turn));
+            }
+        }
+        if let Some((k, _)) = best
+            && !self.is_deliberate_hook(&points[..=k], &points[k..])
+        {
+            points.drain(..k);
+        }
+    }
+
+    fn is_deliberate_hook(&self, hook: &[PenPoint], rest: &[PenPoint]) -> bool {
+        let slow = self.max_hook_duration > 0.0
+            && match (hook[0].time, hook[hook.len() - 1].time) {
+                // the points are reversed for the hook at the end
+                (Some(t0), Some(t1)) => (t1 - t0).abs() > self.max_hook_duration,
+                _ => false,
+            };
+        let pressed = self.max_hook_pressure > 0.0
+            && match (mean_pressure(hook), mean_pressure(rest)) {
+                (Some(hook), Some(rest)) => hook > self.max_hook_pressure * rest,
+                _ => false,
+            };
+        slow || pressed
+    }
+}
+
+impl Default for CleanupParams {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Applies [`CleanupParams::cleanup_stroke`] to every stroke. Strokes are never emptied.
+pub fn cleanup_strokes(strokes: &[Stroke], params: &CleanupParams) -> Vec<Stroke> {
+    strokes
+        .iter()
+        .map(|stroke| params.cleanup_stroke(stroke))
+        .collect()
+}
+
+/// Applies [`CleanupParams::cleanup_pen_stroke`] to every stroke. Strokes are never emptied.
+pub fn cleanup_pen_strokes(strokes: &[PenStroke], params: &CleanupParams) -> Vec<PenStroke> {
+    strokes
+        .iter()
+        .map(|stroke| params.cleanup_pen_stroke(stroke))
+        .collect()
+}
+
+/// Keeps the first point and every point at least `min_distance` away from the previous kept
+/// point. The last point replaces the last kept one, so that the stroke still ends there.
+fn remove_duplicates(points: &[PenPoint], min_distance: f64) -> Vec<PenPoint> {
+    let Some((first, rest)) = points.split_first() else {
+        return vec![];
+    };
+    let mut kept = vec![first.clone()];
+    for p in rest {
+        if distance(kept.last().unwrap(), p) >= min_distance {
+            kept.push(p.clone());
+        }
+    }
+    let last = points.last().unwrap();
+    if kept.len() > 1 {
+        *kept.last_mut().unwrap() = last.clone();
+    } else if distance(first, last) > 0.0 {
+        kept.push(last.clone());
+    }
+    kept
+}
+
+/// Arc length from the start to each point.
+fn arc_lengths(points: &[PenPoint]) -> Vec<f64> {
+    let mut arc = Vec::with_capacity(points.len());
+    let mut length = 0.0;
+    for (i, p) in points.iter().enumerate() {
+        if i > 0 {
+            length += distance(&points[i - 1], p);
+        }
+        arc.push(length);
+    }
+    arc
+}
+
+/// Mean pressure of the points, if they all have one.
+fn mean_pressure(points: &[PenPoint]) -> Option<f64> {
+    let sum = points.iter().map(|p| p.pressure).sum::<Option<f64>>()?;
+    Some(sum / points.len() as f64)
+}
+
+/// Points every `step` along the polyline, from the start to the end inclusive.
+fn resample(points: &[PenPoint], step: f64) -> Vec<PenPoint> {
+    let (Some(first), Some(last)) = (points.first(), points.last()) else {
+        return vec![];
+    };
+    let mut resampled = vec![first.clone()];
+    // distance along the polyline to the next sample
+    let mut next = step;
+    let mut walked = 0.0;
+    for w in points.windows(2) {
+        let len = distance(&w[0], &w[1]);
+        while next <= walked + len {
+            let t = (next - walked) / len;
+            let lerp = |a: Option<f64>, b: Option<f64>| Some(a? + t * (b? - a?));
+            resampled.push(PenPoint {
+                x: w[0].x + t * (w[1].x - w[0].x),
+                y: w[0].y + t * (w[1].y - w[0].y),
+                pressure: lerp(w[0].pressure, w[1].pressure),
+                time: lerp(w[0].time, w[1].time),
+            });
+            next += step;
+        }
+        walked += len;
+    }
+    // the last sample is replaced by the end point if it is too close to it
+    if resampled.len() > 1 && distance(resampled.last().unwrap(), last) < step / 2.0 {
+        resampled.pop();
+    }
+    if points.len() > 1 {
+        resampled.push(last.clone());
+    }
+    resampled
+}
+
+/// Moving average over `radius` neighbors on each side, shrinking the window near the ends so
+/// that they stay in place, and with time data as the pen speed approaches `max_speed`.
+fn smooth(points: &[PenPoint], radius: usize, max_speed: f64) -> Vec<PenPoint> {
+    let n = points.len();
+    (0..n)
+        .map(|i| {
+            let mut r = radius;
+            if let Some(speed) = speed_at(points, i).filter(|_| max_speed > 0.0) {
+                r = (r as f64 * (1.0 - speed / max_speed).max(0.0)).round() as usize;
+            }
+            let r = r.min(i).min(n - 1 - i);
+            let window = &points[i - r..=i + r];
+            let (sx, sy) = window
+                .iter()
+                .fold((0.0, 0.0), |(sx, sy), p| (sx + p.x, sy + p.y));
+            PenPoint {
+                x: sx / window.len() as f64,
+                y: sy / window.len() as f64,
+                ..points[i].clone()
+            }
+        })
+        .collect()
+}
+
+/// Pen speed at the `i`-th point, over the segments next to it.
+fn speed_at(points: &[PenPoint], i: usize) -> Option<f64> {
+    let p = &points[i.saturating_sub(1)];
+    let q = &points[(i + 1).min(points.len() - 1)];
+    let duration = q.time? - p.time?;
+    (duration > 0.0).then(|| distance(p, q) / duration)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stroke::Point;
+
+    /// Every step disabled.
+    const NONE: CleanupParams = CleanupParams {
+        min_point_distance: 0.0,
+        max_hook_length: 0.0,
+        min_hook_angle: 0.0,
+        max_hook_duration: 0.0,
+        max_hook_pressure: 0.0,
+        resample_step: 0.0,
+        smoothing_radius: 0,
+        smoothing_speed: 0.0,
+    };
+    const HOOKS: CleanupParams = CleanupParams {
+        max_hook_length: 8.0,
+        min_hook_angle: PI / 2.0,
+        ..NONE
+    };
+
+    fn pen_stroke(points: &[(f64, f64)]) -> PenStroke {
+        PenStroke(points.iter().map(|&(x, y)| Point { x, y }.into()).collect())
+    }
+
+    fn coordinates(stroke: &PenStroke) -> Vec<(f64, f64)> {
+        stroke.0.iter().map(|p| (p.x, p.y)).collect()
+    }
+
+    /// A horizontal stroke with a hook at each end, as left by the pen landing and lifting.
+    fn hooked() -> PenStroke {
+        let mut points = vec![(5.0, -5.0)];
+        points.extend((0..=10).map(|i| (10.0 * i as f64, 0.0)));
+        points.push((96.0, 5.0));
+        pen_stroke(&points)
+    }
+
+    #[test]
+    fn duplicate_points_are_dropped() {
+        let params = CleanupParams {
+            min_point_distance: 1.0,
+            ..NONE
+        };
+        let stroke = pen_stroke(&[
+            (0.0, 0.0),
+            (0.3, 0.0),
+            (10.0, 0.0),
+            (10.5, 0.2),
+            (20.0, 0.0),
+        ]);
+        assert_eq!(
+            coordinates(&params.cleanup_pen_stroke(&stroke)),
+            [(0.0, 0.0), (10.0, 0.0), (20.0, 0.0)]
+        );
+    }
+
+    #[test]
+    fn hooks_at_the_ends_are_cut() {
+        let cleaned = HOOKS.cleanup_pen_stroke(&hooked());
+        let expected: Vec<_> = (0..=10).map(|i| (10.0 * i as f64, 0.0)).collect();
+        assert_eq!(coordinates(&cleaned), expected);
+        // too short for its ends to be hooks
+        let short = pen_stroke(&[(5.0, -5.0), (0.0, 0.0), (10.0, 0.0)]);
+        assert_eq!(
+            coordinates(&HOOKS.cleanup_pen_stroke(&short)),
+            coordinates(&short)
+        );
+    }
+
+    #[test]
+    fn slow_hooks_are_deliberate() {
+        let params = CleanupParams {
+            max_hook_duration: 40.0,
+            ..HOOKS
+        };
+        let mut stroke = hooked();
+        // 100 ms for the first piece, 10 ms for each of the others
+        for (i, p) in stroke.0.iter_mut().enumerate() {
+            p.time = Some(if i == 0 { 0.0 } else { 90.0 + 10.0 * i as f64 });
+        }
+        let cleaned = params.cleanup_pen_stroke(&stroke);
+        assert_eq!(cleaned.0.first().map(|p| (p.x, p.y)), Some((5.0, -5.0)));
+        assert_eq!(cleaned.0.last().map(|p| (p.x, p.y)), Some((100.0, 0.0)));
+    }
+}
@@ -0,0 +1,40 @@
+use crate::stroke::{Point, Stroke};
+
+/// Number of points sampled between the ends of a curve.
+const N_SAMPLES: i32 = 4;
+
+/// Flattens a quadratic Bézier curve into a stroke of its ends and [`N_SAMPLES`] points at
+/// evenly spaced parameters in between.
+pub fn quadratic_bezier_stroke(start: Point, control: Point, end: Point) -> Stroke {
+    let mut points = vec![start.clone()];
+    let Point { x: sx, y: sy } = start;
+    let Point { x: cx, y: cy } = control;
+    let Point { x: ex, y: ey } = end;
+    for i in 1..=N_SAMPLES {
+        let t = i as f64 / (N_SAMPLES + 1) as f64;
+        let s = 1.0 - t;
+        let x = s * s * sx + 2.0 * s * t * cx + t * t * ex;
+        let y = s * s * sy + 2.0 * s * t * cy + t * t * ey;
+        points.push((x, y).into());
+    }
+    points.push(end);
+    Stroke(points)
+}
+
+/// Flattens a cubic Bézier curve the same way as [`quadratic_bezier_stroke`].
+pub fn cubic_bezier_stroke(start: Point, control1: Point, control2: Point, end: Point) -> Stroke {
+    let mut points = vec![start.clone()];
+    let Point { x: sx, y: sy } = start;
+    let Point { x: c1x, y: c1y } = control1;
+    let Point { x: c2x, y: c2y } = control2;
+    let Point { x: ex, y: ey } = end;
+    for i in 1..=N_SAMPLES {
+        let t = i as f64 / (N_SAMPLES + 1) as f64;
+        let s = 1.0 - t;
+        let x = s * s * s * sx + 3.0 * s * s * t * c1x + 3.0 * s * t * t * c2x + t * t * t * ex;
+        let y = s * s * s * sy + 3.0 * s * s * t * c1y + 3.0 * s * t * t * c2y + t * t * t * ey;
+        points.push((x, y).into());
+    }
+    points.push(end);
+    Stroke(points)
+}
@@ -0,0 +1,148 @@
+use std::borrow::Cow;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    canvas::CanvasSpec,
+    extractor::{ExtractorParams, FeatureExtractor},
+    indexed_feature::{Feature, IndexedFeatureDim, IndexedFeatureElement},
+    normalize::{Normalization, normalize_strokes},
+    quantize::Quantization,
+    stroke::Stroke,
+};
+
+/// Configuration of the directional element feature (DEF) model.
+///
+/// The strokes are densely sampled along their polylines, and every sample is rendered onto a
+/// grid of zones crossed with directions. Unlike the segment model, every part of a stroke
+/// contributes, not just its summary points.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DefParams {
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub normalization: Normalization,
+    /// Zones on each of the x and y axes.
+    pub n_zones: usize,
+    /// Direction planes. They cover the whole circle if `directed`, half of it otherwise.
+    pub n_directions: usize,
+    pub directed: bool,
+    /// Maximum distance between consecutive samples along a stroke.
+    pub sample_step: f64,
+    /// Contribution of an ink length equal to the frame size.
+    pub weight: f64,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub quantization: Quantization,
+}
+
+impl DefParams {
+    pub const DEF1: DefParams = DefParams {
+        normalization: Normalization::Moment {
+            preserve_aspect_ratio: true,
+            spread: 45.0,
+        },
+        n_zones: 8,
+        n_directions: 4,
+        directed: false,
+        sample_step: 2.0,
+        weight: 10.0,
+        quantization: Quantization::SEGMENT_MODEL,
+    };
+}
+
+const DEF_FEATURE_DIM: usize = 3;
+struct DefFeatureDim<'a>(&'a DefParams);
+impl IndexedFeatureDim<DEF_FEATURE_DIM> for DefFeatureDim<'_> {
+    fn dim(&self) -> [usize; DEF_FEATURE_DIM] {
+        let params = self.0;
+        [params.n_zones, params.n_zones, params.n_directions]
+    }
+
+    fn periodic(&self) -> [bool; DEF_FEATURE_DIM] {
+        [false, false, true]
+    }
+}
+type DefFeatureElement = IndexedFeatureElement<DEF_FEATURE_DIM>;
+
+#[derive(Debug, Clone)]
+pub struct DefModel {
+    version: Cow<'static, str>,
+    params: DefParams,
+}
+
+impl DefModel {
+    pub const fn new(version: Cow<'static, str>, params: DefParams) -> Self {
+        DefModel { version, params }
+    }
+
+    pub fn params(&self) -> &DefParams {
+        &self.params
+    }
+
+    fn sample_elements(&self, stroke: &Stroke, elements: &mut Vec<DefFeatureElement>) {
+        let params = &self.params;
+        let frame = CanvasSpec::GLYPHWIKI;
+        for (p, q) in stroke.0.iter().zip(stroke.0.iter().skip(1)) {
+            let (dx, dy) = (q.x - p.x, q.y - p.y);
+            let len = dx.hypot(dy);
+            if len == 0.0 {
+                continue;
+            }
+            let angle = dx.atan2(dy);
+            let direction = if params.directed {
+                angle / std::f64::consts::TAU
+            } else {
+                angle / std::f64::consts::PI
+            };
+            let n_samples = (len / params.sample_step).ceil().max(1.0);
+            let value = len / n_samples / frame.width * params.weight;
+            for i in 0..n_samples as usize {
+                let t = (i as f64 + 0.5) / n_samples;
+                elements.push(DefFeatureElement {
+                    index: [
+                        (p.x + t * dx - frame.origin_x) / frame.width,
+                        (p.y + t * dy - frame.origin_y) / frame.height,
+                        direction,
+                    ],
+                    value,
+                });
+            }
+        }
+    }
+}
+
+/// The registered versions of the DEF model, oldest first.
+pub(crate) static DEF_MODELS: [DefModel; 1] =
+    [DefModel::new(Cow::Borrowed("def-1"), DefParams::DEF1)];
+
+impl FeatureExtractor for DefModel {
+    fn model_version(&self) -> &str {
+        &self.version
+    }
+
+    fn feature_colsize(&self) -> usize {
+        DefFeatureDim(&self.params).colsize()
+    }
+
+    fn extractor_params(&self) -> Option<ExtractorParams<'_>> {
+        Some(ExtractorParams::Def(&self.params))
+    }
+
+    fn quantization(&self) -> Quantization {
+        self.params.quantization
+    }
+
+    fn strokes_to_feature_array(&self, strokes: &[Stroke]) -> Vec<f64> {
+        let strokes = normalize_strokes(strokes, &self.params.normalization);
+        let mut elements = Vec::new();
+        for stroke in strokes.iter() {
+            self.sample_elements(stroke, &mut elements);
+        }
+        DefFeatureDim(&self.params).generate_feature_array(&elements)
+    }
+
+    fn stroke_feature_array(&self, stroke: &Stroke) -> Option<Vec<f64>> {
+        (self.params.normalization == Normalization::None)
+            .then(|| self.strokes_to_feature_array(std::slice::from_ref(stroke)))
+    }
+}
@@ -0,0 +1,96 @@
+use std::collections::BTreeMap;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{canvas::CanvasSpec, stroke::Stroke};
+
+/// Strokes with the canvas they were drawn on, the unit of exchange between tools.
+///
+/// With the `serde` feature, a drawing is serialized in the format described by
+/// `model/drawing.schema.json`:
+///
+/// ```json
+/// {
+///   "version": 1,
+///   "canvas": { "width": 200, "height": 200 },
+///   "strokes": [[[20, 100], [180, 100]]],
+///   "metadata": { "name": "u4e00" }
+/// }
+/// ```
+///
+/// `canvas` defaults to GlyphWiki's 200x200 frame and `metadata` to an empty object. Documents
+/// of other versions are rejected.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(into = "DrawingJson", try_from = "DrawingJson")
+)]
+pub struct Drawing {
+    pub canvas: CanvasSpec,
+    pub strokes: Vec<Stroke>,
+    /// Free-form annotations, such as the glyph name or the writer of a sample.
+    pub metadata: BTreeMap<String, String>,
+}
+
+impl Drawing {
+    /// Version of the JSON format written by this crate.
+    pub const FORMAT_VERSION: u32 = 1;
+
+    pub fn new(canvas: CanvasSpec, strokes: Vec<Stroke>) -> Self {
+        Drawing {
+            canvas,
+            strokes,
+            metadata: BTreeMap::new(),
+        }
+    }
+
+    /// The strokes mapped into [`CanvasSpec::GLYPHWIKI`], as the feature extractors take them.
+    pub fn strokes_in_glyphwiki(&self) -> Vec<Stroke> {
+        self.canvas.strokes_to_glyphwiki(&self.strokes)
+    }
+}
+
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+struct DrawingJson {
+    version: u32,
+    #[serde(default)]
+    canvas: CanvasSpec,
+    strokes: Vec<Stroke>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    metadata: BTreeMap<String, String>,
+}
+
+#[cfg(feature = "serde")]
+impl From<Drawing> for DrawingJson {
+    fn from(drawing: Drawing) -> Self {
+        DrawingJson {
+            version: Drawing::FORMAT_VERSION,
+            canvas: drawing.canvas,
+            strokes: drawing.strokes,
+            metadata: drawing.metadata,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<DrawingJson> for Drawing {
+    type Error = String;
+
+    fn try_from(json: DrawingJson) -> Result<Self, Self::Error> {
+        if json.version != Drawing::FORMAT_VERSION {
+            return Err(format!(
+                "unsupported drawing format version {} (expected {})",
+                json.version,
+                Drawing::FORMAT_VERSION
+            ));
+        }
+        Ok(Drawing {
+            canvas: json.canvas,
+            strokes: json.strokes,
+            metadata: json.metadata,
+        })
+    }
+}
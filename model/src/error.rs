@@ -0,0 +1,80 @@
+use std::fmt;
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
+#[cfg(feature = "js")]
+use tsify::Tsify;
+
+/// Invalid input to the feature extraction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(tag = "kind", rename_all = "snake_case"))]
+#[cfg_attr(feature = "js", derive(Tsify))]
+pub enum ModelError {
+    /// The stroke at index `stroke` has no points.
+    EmptyStroke { stroke: usize },
+    /// A coordinate of the `point`-th point of the `stroke`-th stroke is NaN or infinite.
+    NonFiniteCoordinate { stroke: usize, point: usize },
+    /// The pressure or time of the `point`-th point of the `stroke`-th stroke is NaN or
+    /// infinite.
+    NonFinitePenData { stroke: usize, point: usize },
+    /// A flattened stroke array ended while reading the element at `offset`.
+    UnexpectedEnd { offset: usize },
+    /// A count in a flattened stroke array at `offset` is negative.
+    InvalidCount { offset: usize, count: i32 },
+    /// An array of interleaved coordinates has an odd length.
+    OddCoordinateCount { len: usize },
+    /// The start offset of the `stroke`-th stroke is before the start of the previous stroke or
+    /// past the last point.
+    InvalidOffset { stroke: usize, offset: usize },
+    /// The canvas has a non-positive or non-finite size, or a non-finite origin.
+    InvalidCanvas,
+    /// The model parameter at `field` (e.g. `rel.extra_levels[0].n_angle`) is out of range.
+    InvalidParams { field: String },
+}
+
+impl fmt::Display for ModelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ModelError::EmptyStroke { stroke } => write!(f, "stroke {} has no points", stroke),
+            ModelError::NonFiniteCoordinate { stroke, point } => write!(
+                f,
+                "stroke {} has a non-finite coordinate at point {}",
+                stroke, point
+            ),
+            ModelError::NonFinitePenData { stroke, point } => write!(
+                f,
+                "stroke {} has non-finite pen data at point {}",
+                stroke, point
+            ),
+            ModelError::UnexpectedEnd { offset } => {
+                write!(
+                    f,
+                    "flattened strokes ended unexpectedly at offset {}",
+                    offset
+                )
+            }
+            ModelError::InvalidCount { offset, count } => write!(
+                f,
+                "flattened strokes have an invalid count {} at offset {}",
+                count, offset
+            ),
+            ModelError::OddCoordinateCount { len } => {
+                write!(f, "coordinate array has an odd length {}", len)
+            }
+            ModelError::InvalidOffset { stroke, offset } => {
+                write!(
+                    f,
+                    "stroke {} has an invalid start offset {}",
+                    stroke, offset
+                )
+            }
+            ModelError::InvalidCanvas => write!(f, "invalid canvas specification"),
+            ModelError::InvalidParams { field } => {
+                write!(f, "invalid model parameter {}", field)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ModelError {}
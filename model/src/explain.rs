@@ -0,0 +1,134 @@
+#[cfg(feature = "serde")]
+use serde::Serialize;
+#[cfg(feature = "js")]
+use tsify::Tsify;
+
+/// A feature vector with the contributions of the elements it was rendered from (see
+/// [`crate::FeatureExtractor::explain_feature_array`]).
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "js", derive(Tsify), tsify(into_wasm_abi))]
+pub struct Explanation {
+    pub feature: Vec<f64>,
+    pub elements: Vec<ElementExplanation>,
+}
+
+/// One element rendered onto the absolute or relative grids.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "js", derive(Tsify))]
+pub struct ElementExplanation {
+    pub block: FeatureBlock,
+    /// Index of the stroke the element comes from, after the cleanup and segmentation of the
+    /// model, if any.
+    pub stroke: usize,
+    pub role: SegmentRole,
+    /// Position of the element on the axes of its grid, before it is mapped onto the cells.
+    ///
+    /// The cells of a linear axis span `[0, 1]`, and positions outside it are clamped to the
+    /// end cells. These are the coordinates in the frame (the endpoints for the absolute block,
+    /// the middle point for the relative one), the segment length relative to 1.25 times the
+    /// frame, and a [`crate::AngleAxis::Clamped`] angle, which is in `(-1/3, 1]` for directed
+    /// segments and in `[0, 1)` for undirected ones.
+    ///
+    /// A [`crate::AngleAxis::Periodic`] angle is taken modulo 1 instead. It is measured in
+    /// turns, in `(-1/2, 1/2]`, for directed segments and in half turns, in `(-1, 1]`, for
+    /// undirected ones.
+    pub index: Vec<f64>,
+    pub value: f64,
+    /// The cells the element adds the most to, largest first.
+    pub top_cells: Vec<CellContribution>,
+}
+
+/// Which segment of the summary of a stroke an element comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "js", derive(Tsify))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum SegmentRole {
+    /// The chord from the start to the end of the stroke.
+    StartEnd,
+    /// The first half of a summary with a single mid point.
+    StartMid,
+    /// The second half of a summary with a single mid point.
+    MidEnd,
+    /// The `n`-th piece of a summary with several corner points.
+    Piece(usize),
+}
+
+/// The part of the feature vector a cell belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "js", derive(Tsify))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum FeatureBlock {
+    Abs,
+    Rel,
+    Global,
+    StrokeTypes,
+    StrokeRelations,
+}
+
+/// What a cell of the feature vector stands for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "js", derive(Tsify))]
+pub struct CellLabel {
+    pub block: FeatureBlock,
+    /// Level of the multi-resolution grid, 0 being the main grid.
+    pub level: usize,
+    /// Coordinates of the cell on the axes of its grid; for the blocks without a single grid,
+    /// the offset of the cell in the block.
+    pub coords: Vec<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "js", derive(Tsify))]
+pub struct CellContribution {
+    /// Index into the feature vector.
+    pub cell: usize,
+    pub label: Option<CellLabel>,
+    pub contribution: f64,
+}
+
+/// A cell that differs between two feature vectors.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "js", derive(Tsify))]
+pub struct CellDifference {
+    /// Index into the feature vectors.
+    pub cell: usize,
+    /// What the cell stands for, if the vectors were compared by a model (see
+    /// [`crate::FeatureExtractor::compare_feature_arrays`]).
+    pub label: Option<CellLabel>,
+    pub a: f64,
+    pub b: f64,
+    /// Fraction of the squared L2 distance between the vectors due to this cell.
+    pub share: f64,
+}
+
+/// The `top` cells accounting for most of the L2 distance between `a` and `b`, largest first.
+/// The vectors are compared up to the length of the shorter one. The cells are not labeled.
+pub fn compare_feature_arrays(a: &[f64], b: &[f64], top: usize) -> Vec<CellDifference> {
+    let total: f64 = a.iter().zip(b).map(|(x, y)| (x - y).powi(2)).sum();
+    let mut differences: Vec<_> = a
+        .iter()
+        .zip(b)
+        .enumerate()
+        .map(|(cell, (&a, &b))| CellDifference {
+            cell,
+            label: None,
+            a,
+            b,
+            share: if total > 0.0 {
+                (a - b).powi(2) / total
+            } else {
+                0.0
+            },
+        })
+        .collect();
+    differences.sort_by(|x, y| y.share.total_cmp(&x.share).then(x.cell.cmp(&y.cell)));
+    differences.truncate(top);
+    differences
+}
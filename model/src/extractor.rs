@@ -0,0 +1,157 @@
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+use crate::{
+    augment::AugmentParams,
+    canvas::CanvasSpec,
+    def::{DEF_MODELS, DefParams},
+    error::ModelError,
+    explain::{CellDifference, CellLabel, Explanation},
+    model::SEGMENT_MODELS,
+    params::ModelParams,
+    quantize::Quantization,
+    stroke::{PenStroke, Stroke, check_pen_strokes, check_strokes},
+};
+
+/// A pipeline that turns strokes into a fixed-length feature vector.
+///
+/// Indexes built with one model version can only be searched with features computed by the
+/// same version, so each extractor is identified by its version string.
+pub trait FeatureExtractor: Sync {
+    fn model_version(&self) -> &str;
+
+    fn feature_colsize(&self) -> usize;
+
+    /// The parameters the extractor was configured with, to be recorded alongside an index.
+    fn extractor_params(&self) -> Option<ExtractorParams<'_>> {
+        None
+    }
+
+    /// Mapping between the features of this model and their quantized form.
+    fn quantization(&self) -> Quantization;
+
+    /// `strokes` are in the [`CanvasSpec::GLYPHWIKI`] frame.
+    ///
+    /// Panics on invalid strokes; use [`Self::try_strokes_to_feature_array`] for untrusted input.
+    fn strokes_to_feature_array(&self, strokes: &[Stroke]) -> Vec<f64>;
+
+    fn try_strokes_to_feature_array(&self, strokes: &[Stroke]) -> Result<Vec<f64>, ModelError> {
+        check_strokes(strokes)?;
+        Ok(self.strokes_to_feature_array(strokes))
+    }
+
+    /// Same as [`Self::strokes_to_feature_array`] for pen strokes. Models whose preprocessing
+    /// makes use of the pen data override this; the others only see the positions.
+    fn pen_strokes_to_feature_array(&self, strokes: &[PenStroke]) -> Vec<f64> {
+        let strokes: Vec<_> = strokes.iter().map(Stroke::from).collect();
+        self.strokes_to_feature_array(&strokes)
+    }
+
+    fn try_pen_strokes_to_feature_array(
+        &self,
+        strokes: &[PenStroke],
+    ) -> Result<Vec<f64>, ModelError> {
+        check_pen_strokes(strokes)?;
+        Ok(self.pen_strokes_to_feature_array(strokes))
+    }
+
+    /// If the feature of any strokes is the sum of the features of each stroke, returns the
+    /// feature of a single `stroke`. This allows updating the feature incrementally (see
+    /// [`crate::FeatureAccumulator`]).
+    fn stroke_feature_array(&self, _stroke: &Stroke) -> Option<Vec<f64>> {
+        None
+    }
+
+    /// Same as [`Self::try_strokes_to_feature_array`] for strokes drawn on `canvas`.
+    fn try_strokes_to_feature_array_on_canvas(
+        &self,
+        strokes: &[Stroke],
+        canvas: &CanvasSpec,
+    ) -> Result<Vec<f64>, ModelError> {
+        canvas.check()?;
+        check_strokes(strokes)?;
+        Ok(self.strokes_to_feature_array(&canvas.strokes_to_glyphwiki(strokes)))
+    }
+
+    /// Same as [`Self::try_pen_strokes_to_feature_array`] for strokes drawn on `canvas`.
+    fn try_pen_strokes_to_feature_array_on_canvas(
+        &self,
+        strokes: &[PenStroke],
+        canvas: &CanvasSpec,
+    ) -> Result<Vec<f64>, ModelError> {
+        canvas.check()?;
+        check_pen_strokes(strokes)?;
+        Ok(self.pen_strokes_to_feature_array(&canvas.pen_strokes_to_glyphwiki(strokes)))
+    }
+
+    /// The feature of `strokes` along with the elements it was rendered from and the cells they
+    /// contribute to the most (`top_cells` per element), for debugging. `None` if the model
+    /// cannot explain its features.
+    ///
+    /// Panics on invalid strokes, as [`Self::strokes_to_feature_array`] does.
+    fn explain_feature_array(&self, _strokes: &[Stroke], _top_cells: usize) -> Option<Explanation> {
+        None
+    }
+
+    /// What the `cell`-th element of the feature stands for, if known.
+    fn cell_label(&self, _cell: usize) -> Option<CellLabel> {
+        None
+    }
+
+    /// Same as [`crate::compare_feature_arrays`], with the cells labeled by
+    /// [`Self::cell_label`].
+    fn compare_feature_arrays(&self, a: &[f64], b: &[f64], top: usize) -> Vec<CellDifference> {
+        let mut differences = crate::explain::compare_feature_arrays(a, b, top);
+        for difference in &mut differences {
+            difference.label = self.cell_label(difference.cell);
+        }
+        differences
+    }
+
+    /// The features of `n` perturbed copies of `strokes` (see [`AugmentParams::augment`]).
+    fn try_augmented_feature_arrays(
+        &self,
+        strokes: &[Stroke],
+        augment: &AugmentParams,
+        n: usize,
+        seed: u64,
+    ) -> Result<Vec<Vec<f64>>, ModelError> {
+        check_strokes(strokes)?;
+        Ok(augment
+            .augment(strokes, n, seed)
+            .iter()
+            .map(|strokes| self.strokes_to_feature_array(strokes))
+            .collect())
+    }
+}
+
+/// Parameters of one of the kinds of extractors.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(untagged))]
+pub enum ExtractorParams<'a> {
+    Segment(&'a ModelParams),
+    Def(&'a DefParams),
+}
+
+/// The model version used when the caller does not ask for a specific one.
+pub const DEFAULT_MODEL_VERSION: &str = "2";
+
+/// All the registered extractors, oldest version first.
+pub fn extractors() -> impl Iterator<Item = &'static dyn FeatureExtractor> {
+    let segment_models = SEGMENT_MODELS
+        .iter()
+        .map(|model| model as &dyn FeatureExtractor);
+    let def_models = DEF_MODELS
+        .iter()
+        .map(|model| model as &dyn FeatureExtractor);
+    segment_models.chain(def_models)
+}
+
+pub fn get_extractor(version: &str) -> Option<&'static dyn FeatureExtractor> {
+    extractors().find(|extractor| extractor.model_version() == version)
+}
+
+pub fn default_extractor() -> &'static dyn FeatureExtractor {
+    get_extractor(DEFAULT_MODEL_VERSION).expect("default model version is not registered")
+}
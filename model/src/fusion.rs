@@ -0,0 +1,86 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+/// How the kNN results of several queries (e.g. augmented copies of a query) are merged into a
+/// single ranking.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case", tag = "method"))]
+pub enum Fusion {
+    /// Each item scores its smallest distance over the lists; lower is better.
+    MinDistance,
+    /// Each item scores the sum of `1 / (k + rank)` over the lists it appears in, with ranks
+    /// starting at 1; higher is better. Only the order within each list matters.
+    ReciprocalRank { k: f64 },
+}
+
+impl Fusion {
+    /// The usual constant of reciprocal rank fusion.
+    pub const RECIPROCAL_RANK: Fusion = Fusion::ReciprocalRank { k: 60.0 };
+
+    /// Merges result lists of `(item, distance)`, each sorted by ascending distance. Returns
+    /// every item once with its score, best first.
+    pub fn fuse<K: Eq + Hash + Clone>(&self, lists: &[Vec<(K, f64)>]) -> Vec<(K, f64)> {
+        // first-seen order of the items, so that ties are broken deterministically
+        let mut scores: HashMap<&K, (usize, f64)> = HashMap::new();
+        for list in lists {
+            for (rank, (item, distance)) in list.iter().enumerate() {
+                let next = scores.len();
+                match *self {
+                    Fusion::MinDistance => {
+                        let (_, score) = scores.entry(item).or_insert((next, f64::INFINITY));
+                        *score = score.min(*distance);
+                    }
+                    Fusion::ReciprocalRank { k } => {
+                        let (_, score) = scores.entry(item).or_insert((next, 0.0));
+                        *score += 1.0 / (k + (rank + 1) as f64);
+                    }
+                }
+            }
+        }
+
+        let mut fused: Vec<_> = scores.into_iter().collect();
+        fused.sort_by(|(_, (i, a)), (_, (j, b))| {
+            let by_score = match self {
+                Fusion::MinDistance => a.total_cmp(b),
+                Fusion::ReciprocalRank { .. } => b.total_cmp(a),
+            };
+            by_score.then(i.cmp(j))
+        });
+        fused
+            .into_iter()
+            .map(|(item, (_, score))| (item.clone(), score))
+            .collect()
+    }
+}
+
+/// Result of [`crate::fuse_search_results`]: the fused items, best first, with their scores.
+#[wasm_bindgen]
+pub struct FusedResults {
+    names: Vec<String>,
+    scores: Vec<f64>,
+}
+
+impl FusedResults {
+    pub(crate) fn new(fused: Vec<(String, f64)>) -> Self {
+        let (names, scores) = fused.into_iter().unzip();
+        FusedResults { names, scores }
+    }
+}
+
+#[wasm_bindgen]
+impl FusedResults {
+    #[wasm_bindgen(getter)]
+    pub fn names(&self) -> Vec<String> {
+        self.names.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn scores(&self) -> Box<[f64]> {
+        self.scores.clone().into()
+    }
+}
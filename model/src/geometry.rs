@@ -0,0 +1,83 @@
+//! Geometry shared by the preprocessing steps and the feature blocks.
+
+use std::f64::consts::PI;
+
+use crate::stroke::{PenPoint, Point};
+
+/// A point of the plane, with or without the pen data.
+pub(crate) trait Position {
+    fn xy(&self) -> (f64, f64);
+}
+
+impl Position for Point {
+    fn xy(&self) -> (f64, f64) {
+        (self.x, self.y)
+    }
+}
+
+impl Position for PenPoint {
+    fn xy(&self) -> (f64, f64) {
+        (self.x, self.y)
+    }
+}
+
+impl<P: Position> Position for &P {
+    fn xy(&self) -> (f64, f64) {
+        (*self).xy()
+    }
+}
+
+pub(crate) fn distance(p: &impl Position, q: &impl Position) -> f64 {
+    let ((px, py), (qx, qy)) = (p.xy(), q.xy());
+    (qx - px).hypot(qy - py)
+}
+
+/// The smallest axis-aligned rectangle containing some points.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct BoundingBox {
+    pub min_x: f64,
+    pub max_x: f64,
+    pub min_y: f64,
+    pub max_y: f64,
+}
+
+impl BoundingBox {
+    /// The bounding box of `points`, or `None` if there are none.
+    pub fn of<'a>(points: impl IntoIterator<Item = &'a Point>) -> Option<Self> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+        let mut bbox = BoundingBox {
+            min_x: first.x,
+            max_x: first.x,
+            min_y: first.y,
+            max_y: first.y,
+        };
+        for p in points {
+            bbox.min_x = bbox.min_x.min(p.x);
+            bbox.max_x = bbox.max_x.max(p.x);
+            bbox.min_y = bbox.min_y.min(p.y);
+            bbox.max_y = bbox.max_y.max(p.y);
+        }
+        Some(bbox)
+    }
+
+    pub fn width(&self) -> f64 {
+        self.max_x - self.min_x
+    }
+
+    pub fn height(&self) -> f64 {
+        self.max_y - self.min_y
+    }
+
+    pub fn center(&self) -> (f64, f64) {
+        (
+            (self.min_x + self.max_x) / 2.0,
+            (self.min_y + self.max_y) / 2.0,
+        )
+    }
+}
+
+/// Absolute difference of two directions (in radians), in `[0, PI]`.
+pub(crate) fn turn(a: f64, b: f64) -> f64 {
+    ((b - a + PI).rem_euclid(2.0 * PI) - PI).abs()
+}
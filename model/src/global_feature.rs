@@ -0,0 +1,119 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    geometry::{BoundingBox, distance},
+    indexed_feature::{Feature, IndexedFeatureDim, IndexedFeatureElement, PositionDim},
+    model::StrokeBlock,
+    stroke::Stroke,
+};
+
+/// Configuration of the block of global shape descriptors: the stroke count, the total ink
+/// length, the aspect ratio and the centroid of the drawing, each soft-binned on its own axis.
+///
+/// The descriptors are computed from the strokes before normalization, so that the centroid
+/// keeps the position of the drawing. The ink length is measured relative to the longer side of
+/// the bounding box and does not depend on the size of the drawing.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GlobalFeatureParams {
+    /// Value each descriptor adds to its axis.
+    pub weight: f64,
+    pub n_stroke_count: usize,
+    /// Stroke count mapped to the last cell of its axis.
+    pub max_stroke_count: f64,
+    pub n_length: usize,
+    /// Ink length (in units of the longer side of the bounding box) mapped to the last cell.
+    pub max_length: f64,
+    pub n_aspect_ratio: usize,
+    /// Width/height ratio mapped to the last cell; its reciprocal is mapped to the first cell.
+    pub max_aspect_ratio: f64,
+    /// Cells on each of the x and y axes of the centroid.
+    pub n_centroid: usize,
+}
+
+struct ScalarDim(usize);
+impl IndexedFeatureDim<1> for ScalarDim {
+    fn dim(&self) -> [usize; 1] {
+        [self.0]
+    }
+}
+
+impl StrokeBlock for GlobalFeatureParams {
+    fn colsize(&self) -> usize {
+        ScalarDim(self.n_stroke_count).colsize()
+            + ScalarDim(self.n_length).colsize()
+            + ScalarDim(self.n_aspect_ratio).colsize()
+            + PositionDim(self.n_centroid, self.n_centroid).colsize()
+    }
+
+    /// The stroke count, ink length and aspect ratio on their own axes, then the 2D centroid.
+    /// The length and centroid are taken along the polylines; no strokes give all zeros.
+    fn generate_feature_array(&self, strokes: &[Stroke]) -> Vec<f64> {
+        let scalar = |value: f64| {
+            [IndexedFeatureElement {
+                index: [value],
+                value: self.weight,
+            }]
+        };
+
+        let points = || strokes.iter().flat_map(|stroke| &stroke.0);
+        let (width, height) =
+            BoundingBox::of(points()).map_or((0.0, 0.0), |bbox| (bbox.width(), bbox.height()));
+
+        // ink length and centroid along the polylines; a drawing of dots only has no length and
+        // its centroid is the mean of the points
+        let mut length = 0.0;
+        let (mut sum_x, mut sum_y) = (0.0, 0.0);
+        for stroke in strokes {
+            for (p, q) in stroke.0.iter().zip(stroke.0.iter().skip(1)) {
+                let len = distance(p, q);
+                length += len;
+                sum_x += len * (p.x + q.x) / 2.0;
+                sum_y += len * (p.y + q.y) / 2.0;
+            }
+        }
+        let centroid = if length > 0.0 {
+            Some((sum_x / length, sum_y / length))
+        } else {
+            let n = points().count();
+            (n > 0).then(|| {
+                let (sx, sy) = points().fold((0.0, 0.0), |(sx, sy), p| (sx + p.x, sy + p.y));
+                (sx / n as f64, sy / n as f64)
+            })
+        };
+
+        let stroke_count = (strokes.len() as f64 - 1.0) / (self.max_stroke_count - 1.0);
+        let relative_length = match width.max(height) {
+            0.0 => 0.0,
+            size => length / size / self.max_length,
+        };
+        // log scale, so that w/h and h/w are symmetric around the middle cell
+        let aspect_ratio = match (width, height) {
+            (0.0, 0.0) => 0.5,
+            (0.0, _) => 0.0,
+            (_, 0.0) => 1.0,
+            (w, h) => ((w / h).ln() / self.max_aspect_ratio.ln() + 1.0) / 2.0,
+        };
+
+        let mut feature_array = Vec::with_capacity(self.colsize());
+        if strokes.is_empty() {
+            feature_array.resize(self.colsize(), 0.0);
+            return feature_array;
+        }
+        feature_array
+            .extend(ScalarDim(self.n_stroke_count).generate_feature_array(&scalar(stroke_count)));
+        feature_array
+            .extend(ScalarDim(self.n_length).generate_feature_array(&scalar(relative_length)));
+        feature_array
+            .extend(ScalarDim(self.n_aspect_ratio).generate_feature_array(&scalar(aspect_ratio)));
+        let centroid: Vec<_> = centroid
+            .map(|(cx, cy)| PositionDim::element(cx, cy, self.weight))
+            .into_iter()
+            .collect();
+        feature_array.extend(
+            PositionDim(self.n_centroid, self.n_centroid).generate_feature_array(&centroid),
+        );
+        feature_array
+    }
+}
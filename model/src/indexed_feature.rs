@@ -1,7 +1,13 @@
-use itertools::Itertools;
+use crate::canvas::CanvasSpec;
 
 pub trait IndexedFeatureDim<const N: usize> {
-    const DIM: [usize; N];
+    fn dim(&self) -> [usize; N];
+
+    /// Whether each axis wraps around (e.g. an angle). The cells of a periodic axis are spread
+    /// over `[0, 1)` instead of `[0, 1]`, and the kernel distance is measured around the circle.
+    fn periodic(&self) -> [bool; N] {
+        [false; N]
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -11,60 +17,191 @@ pub struct IndexedFeatureElement<const N: usize> {
 }
 
 pub trait Feature<const N: usize> {
-    const COLSIZE: usize;
+    fn colsize(&self) -> usize;
 
-    fn generate_feature_array(features: &[IndexedFeatureElement<N>]) -> Vec<f64>;
+    fn generate_feature_array(&self, features: &[IndexedFeatureElement<N>]) -> Vec<f64>;
 }
 
 impl<T: IndexedFeatureDim<N>, const N: usize> Feature<N> for T {
-    // const COLSIZE: usize = T::DIM.iter().product();
-    const COLSIZE: usize = {
-        let len = T::DIM.len();
-        let mut size = 1;
-        let mut i = 0;
-        while i < len {
-            size *= T::DIM[i];
-            i += 1;
+    fn colsize(&self) -> usize {
+        self.dim().iter().product()
+    }
+
+    fn generate_feature_array(&self, features: &[IndexedFeatureElement<N>]) -> Vec<f64> {
+        // The kernel is a product of per-axis Gaussians, so the contribution of each element is
+        // the outer product of its 1-D kernels. The cells are laid out in row-major order (the
+        // last axis varies fastest).
+        let dim = self.dim();
+        let periodic = self.periodic();
+
+        let mut feature_array = vec![0.0; self.colsize()];
+        let mut kernels = dim.map(|d| vec![0.0; d]);
+        let mut contribution = Vec::with_capacity(feature_array.len());
+        let mut contribution_next = Vec::with_capacity(feature_array.len());
+        for f in features {
+            for (axis, kernel) in kernels.iter_mut().enumerate() {
+                let d = dim[axis];
+                let i = if periodic[axis] {
+                    f.index[axis].rem_euclid(1.0) * d as f64
+                } else {
+                    f.index[axis].clamp(0.0, 1.0) * (d - 1) as f64
+                };
+                for (j, k) in kernel.iter_mut().enumerate() {
+                    let distance = axis_distance(i, j as f64, d, periodic[axis]);
+                    *k = (-distance.powi(2)).exp();
+                }
+            }
+
+            contribution.clear();
+            contribution.push(f.value);
+            for kernel in &kernels {
+                contribution_next.clear();
+                contribution_next.extend(
+                    contribution
+                        .iter()
+                        .flat_map(|c| kernel.iter().map(move |k| c * k)),
+                );
+                std::mem::swap(&mut contribution, &mut contribution_next);
+            }
+
+            for (a, c) in feature_array.iter_mut().zip(&contribution) {
+                *a += c;
+            }
         }
-        size
-    };
+        feature_array
+    }
+}
+
+/// The same elements rendered onto several grids (e.g. of different resolutions), each
+/// multiplied by its weight, concatenated in order.
+pub struct Pyramid<T> {
+    pub levels: Vec<(T, f64)>,
+}
 
-    fn generate_feature_array(features: &[IndexedFeatureElement<N>]) -> Vec<f64> {
-        struct MagnifiedFeatureElement {
-            index: Vec<f64>,
-            value: f64,
+impl<T: IndexedFeatureDim<N>, const N: usize> Feature<N> for Pyramid<T> {
+    fn colsize(&self) -> usize {
+        self.levels.iter().map(|(grid, _)| grid.colsize()).sum()
+    }
+
+    fn generate_feature_array(&self, features: &[IndexedFeatureElement<N>]) -> Vec<f64> {
+        let mut feature_array = Vec::with_capacity(self.colsize());
+        for (grid, weight) in &self.levels {
+            feature_array.extend(
+                grid.generate_feature_array(features)
+                    .into_iter()
+                    .map(|x| x * weight),
+            );
         }
+        feature_array
+    }
+}
+
+/// An `n_x` by `n_y` grid over the [`CanvasSpec::GLYPHWIKI`] frame.
+pub(crate) struct PositionDim(pub usize, pub usize);
 
-        let features_magnified: Vec<_> = features
-            .iter()
-            .map(|f| MagnifiedFeatureElement {
-                index: f
-                    .index
-                    .iter()
-                    .zip(T::DIM.iter())
-                    .map(|(i, d)| i.clamp(0.0, 1.0) * (*d - 1) as f64)
-                    .collect(),
-                value: f.value,
-            })
-            .collect();
-
-        T::DIM
-            .into_iter()
-            .map(|d| (0..d).map(|i| i as f64))
-            .multi_cartesian_product()
-            .map(|index| {
-                features_magnified
-                    .iter()
-                    .map(|f| {
-                        f.value
-                            * f.index
-                                .iter()
-                                .zip(index.iter())
-                                .map(|(i, j)| (-(i - j).powi(2)).exp())
-                                .product::<f64>()
-                    })
-                    .sum()
-            })
-            .collect()
+impl IndexedFeatureDim<2> for PositionDim {
+    fn dim(&self) -> [usize; 2] {
+        [self.0, self.1]
+    }
+}
+
+impl PositionDim {
+    /// The element of a point of the frame.
+    pub(crate) fn element(x: f64, y: f64, value: f64) -> IndexedFeatureElement<2> {
+        let frame = CanvasSpec::GLYPHWIKI;
+        IndexedFeatureElement {
+            index: [
+                (x - frame.origin_x) / frame.width,
+                (y - frame.origin_y) / frame.height,
+            ],
+            value,
+        }
+    }
+}
+
+fn axis_distance(i: f64, j: f64, dim: usize, periodic: bool) -> f64 {
+    let distance = (i - j).abs();
+    if periodic {
+        let dim = dim as f64;
+        let distance = distance % dim;
+        distance.min(dim - distance)
+    } else {
+        distance
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A single angle axis of 8 cells, indexed in turns as the directed periodic angle of the
+    /// segment model.
+    struct AngleDim {
+        periodic: bool,
+    }
+
+    impl IndexedFeatureDim<1> for AngleDim {
+        fn dim(&self) -> [usize; 1] {
+            [8]
+        }
+
+        fn periodic(&self) -> [bool; 1] {
+            [self.periodic]
+        }
+    }
+
+    fn feature(dim: &AngleDim, degrees: f64) -> Vec<f64> {
+        dim.generate_feature_array(&[IndexedFeatureElement {
+            index: [degrees / 360.0],
+            value: 1.0,
+        }])
+    }
+
+    fn peak(feature: &[f64]) -> usize {
+        (0..feature.len())
+            .max_by(|&i, &j| feature[i].total_cmp(&feature[j]))
+            .unwrap()
+    }
+
+    #[test]
+    fn periodic_axis_wraps_around() {
+        let dim = AngleDim { periodic: true };
+        // +179° and -179° are 2° apart, across the end of the range of `atan2`
+        let a = feature(&dim, 179.0);
+        let b = feature(&dim, -179.0);
+        assert_eq!(peak(&a), 4);
+        assert_eq!(peak(&b), 4);
+        for (x, y) in a.iter().zip(&b) {
+            assert!((x - y).abs() < 0.1);
+        }
+        // +1° and -1° are on both sides of the first cell, whose neighbours are the second and
+        // the last cells
+        let a = feature(&dim, 1.0);
+        let b = feature(&dim, -1.0);
+        assert_eq!(peak(&a), 0);
+        assert_eq!(peak(&b), 0);
+        assert!((a[1] - b[7]).abs() < 1e-12);
+        assert!((a[7] - b[1]).abs() < 1e-12);
+        for j in 1..8 {
+            assert!((a[j] - a[8 - j]).abs() < 0.1);
+        }
+    }
+
+    #[test]
+    fn linear_axis_is_clamped() {
+        let dim = AngleDim { periodic: false };
+        let a = feature(&dim, 1.0);
+        let b = feature(&dim, 359.0);
+        assert_eq!(peak(&a), 0);
+        assert_eq!(peak(&b), 7);
+        // the ends of the range are as far apart as they can be
+        assert!(a[7] < 1e-12);
+        assert!(b[0] < 1e-12);
+        // out-of-range indexes land on the end cells
+        assert_eq!(feature(&dim, -90.0), feature(&dim, 0.0));
+        assert_eq!(feature(&dim, 720.0), feature(&dim, 360.0));
+        for (j, x) in feature(&dim, 180.0).iter().enumerate() {
+            assert!((x - (-(3.5 - j as f64).powi(2)).exp()).abs() < 1e-12);
+        }
     }
 }
@@ -0,0 +1,510 @@
+//! Reading and writing strokes as [InkML](https://www.w3.org/TR/InkML/).
+
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, Write};
+
+use roxmltree::{Document, Node};
+
+use crate::stroke::{Point, Stroke};
+
+/// The namespace of InkML elements.
+pub const INKML_NAMESPACE: &str = "http://www.w3.org/2003/InkML";
+
+const XML_NAMESPACE: &str = "http://www.w3.org/XML/1998/namespace";
+
+/// Invalid InkML input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InkmlError {
+    /// The document is not well-formed XML.
+    Xml(String),
+    /// The root element is not `<ink>`.
+    NotInk,
+    /// A trace format has no regular channel named `channel` (`X` or `Y`).
+    MissingChannel { channel: &'static str },
+    /// A `contextRef` or `traceFormatRef` does not point to an element of the document.
+    UnresolvedReference { reference: String },
+    /// The `point`-th point of the `trace`-th trace is malformed or lacks a coordinate.
+    InvalidPoint { trace: usize, point: usize },
+}
+
+impl fmt::Display for InkmlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InkmlError::Xml(message) => write!(f, "invalid XML: {}", message),
+            InkmlError::NotInk => write!(f, "root element is not <ink>"),
+            InkmlError::MissingChannel { channel } => {
+                write!(f, "trace format has no {} channel", channel)
+            }
+            InkmlError::UnresolvedReference { reference } => {
+                write!(f, "unresolved reference {}", reference)
+            }
+            InkmlError::InvalidPoint { trace, point } => {
+                write!(f, "trace {} has an invalid point {}", trace, point)
+            }
+        }
+    }
+}
+
+impl std::error::Error for InkmlError {}
+
+/// Positions of the coordinates among the values of a point.
+#[derive(Debug, Clone, Copy)]
+struct TraceFormat {
+    x: usize,
+    y: usize,
+}
+
+impl TraceFormat {
+    /// The format of traces without a context: `X` and `Y`.
+    const DEFAULT: TraceFormat = TraceFormat { x: 0, y: 1 };
+}
+
+/// Parses the pen-down traces of an InkML document into strokes, in document order.
+///
+/// The coordinates are taken from the `X` and `Y` channels of the trace format of each trace,
+/// resolved through `contextRef` and `traceFormatRef`; the other channels are skipped. Points
+/// may use explicit values as well as first (`'`) and second (`"`) differences. Trace groups
+/// are flattened, and the coordinates are returned as they are, in the units of the document.
+pub fn strokes_from_inkml(xml: &str) -> Result<Vec<Stroke>, InkmlError> {
+    let document = Document::parse(xml).map_err(|err| InkmlError::Xml(err.to_string()))?;
+    let ink = document.root_element();
+    if !is_inkml(ink, "ink") {
+        return Err(InkmlError::NotInk);
+    }
+    let reader = InkmlReader {
+        ids: document
+            .descendants()
+            .filter_map(|node| Some((node.attribute((XML_NAMESPACE, "id"))?, node)))
+            .collect(),
+    };
+    let mut strokes = vec![];
+    let mut current = TraceFormat::DEFAULT;
+    reader.read_traces(ink, &mut current, &mut strokes)?;
+    Ok(strokes)
+}
+
+struct InkmlReader<'a, 'input> {
+    ids: HashMap<&'a str, Node<'a, 'input>>,
+}
+
+impl<'a, 'input> InkmlReader<'a, 'input> {
+    /// Collects the traces under `parent`. A `<context>` or `<traceFormat>` among them changes
+    /// the format of the traces that follow it.
+    fn read_traces(
+        &self,
+        parent: Node<'a, 'input>,
+        current: &mut TraceFormat,
+        strokes: &mut Vec<Stroke>,
+    ) -> Result<(), InkmlError> {
+        for node in parent.children().filter(|node| node.is_element()) {
+            if is_inkml(node, "context") {
+                *current = self.context_format(node)?;
+            } else if is_inkml(node, "traceFormat") {
+                *current = parse_trace_format(node)?;
+            } else if is_inkml(node, "traceGroup") {
+                let mut format = match node.attribute("contextRef") {
+                    Some(reference) => self.context_format(self.resolve(reference)?)?,
+                    None => *current,
+                };
+                self.read_traces(node, &mut format, strokes)?;
+            } else if is_inkml(node, "trace") {
+                if node.attribute("type") == Some("penUp") {
+                    continue;
+                }
+                let format = match node.attribute("contextRef") {
+                    Some(reference) => self.context_format(self.resolve(reference)?)?,
+                    None => *current,
+                };
+                let trace = strokes.len();
+                let points = parse_trace(node.text().unwrap_or_default(), format, trace)?;
+                if !points.is_empty() {
+                    strokes.push(Stroke(points));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn context_format(&self, context: Node<'a, 'input>) -> Result<TraceFormat, InkmlError> {
+        if let Some(trace_format) = context
+            .children()
+            .find(|node| is_inkml(*node, "traceFormat"))
+        {
+            return parse_trace_format(trace_format);
+        }
+        if let Some(reference) = context.attribute("traceFormatRef") {
+            return parse_trace_format(self.resolve(reference)?);
+        }
+        if let Some(reference) = context.attribute("contextRef") {
+            return self.context_format(self.resolve(reference)?);
+        }
+        Ok(TraceFormat::DEFAULT)
+    }
+
+    fn resolve(&self, reference: &str) -> Result<Node<'a, 'input>, InkmlError> {
+        reference
+            .strip_prefix('#')
+            .and_then(|id| self.ids.get(id))
+            .copied()
+            .ok_or_else(|| InkmlError::UnresolvedReference {
+                reference: reference.to_string(),
+            })
+    }
+}
+
+fn is_inkml(node: Node, name: &str) -> bool {
+    // documents without the namespace declaration are common enough to accept
+    node.tag_name().name() == name
+        && matches!(node.tag_name().namespace(), None | Some(INKML_NAMESPACE))
+}
+
+/// Locates `X` and `Y` among the regular channels. Intermittent channels come after the
+/// regular ones and are never needed.
+fn parse_trace_format(trace_format: Node) -> Result<TraceFormat, InkmlError> {
+    let channels: Vec<_> = trace_format
+        .children()
+        .filter(|node| is_inkml(*node, "channel"))
+        .collect();
+    let position = |channel: &'static str| {
+        channels
+            .iter()
+            .position(|node| node.attribute("name") == Some(channel))
+            .ok_or(InkmlError::MissingChannel { channel })
+    };
+    Ok(TraceFormat {
+        x: position("X")?,
+        y: position("Y")?,
+    })
+}
+
+/// How a value in a trace relates to the values before it. A qualifier holds for the
+/// following values of the channel until another one is given.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ValueMode {
+    /// `!`: the value itself.
+    Explicit,
+    /// `'`: the difference from the previous value.
+    FirstDifference,
+    /// `"`: the change of that difference.
+    SecondDifference,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Token {
+    Number(f64),
+    /// `T` or `F`, values of boolean channels.
+    Boolean,
+    /// `*`: the value does not change.
+    Unchanged,
+    /// `?`: the value is unknown.
+    Unknown,
+}
+
+/// State of one coordinate channel along a trace.
+#[derive(Debug, Clone, Copy)]
+struct Channel {
+    mode: ValueMode,
+    value: f64,
+    difference: f64,
+}
+
+impl Channel {
+    fn advance(&mut self, mode: Option<ValueMode>, token: Token, first: bool) -> Option<f64> {
+        if let Some(mode) = mode {
+            self.mode = mode;
+        }
+        let previous = self.value;
+        self.value = match (token, self.mode) {
+            (Token::Number(value), ValueMode::Explicit) => value,
+            // differences on the first point are taken from zero
+            (Token::Number(difference), ValueMode::FirstDifference) => previous + difference,
+            (Token::Number(change), ValueMode::SecondDifference) => {
+                previous + self.difference + change
+            }
+            (Token::Unchanged, _) if !first => previous,
+            _ => return None,
+        };
+        self.difference = self.value - previous;
+        Some(self.value)
+    }
+}
+
+/// Parses the text of a `<trace>`: points separated by commas, each a sequence of values
+/// optionally separated by whitespace.
+fn parse_trace(text: &str, format: TraceFormat, trace: usize) -> Result<Vec<Point>, InkmlError> {
+    let mut channels = [Channel {
+        mode: ValueMode::Explicit,
+        value: 0.0,
+        difference: 0.0,
+    }; 2];
+    let mut points = vec![];
+    if text.trim().is_empty() {
+        return Ok(points);
+    }
+    for (point, values) in text.split(',').enumerate() {
+        let invalid = InkmlError::InvalidPoint { trace, point };
+        let values = tokenize(values).ok_or(invalid.clone())?;
+        let mut coordinates = [0.0; 2];
+        for (coordinate, (channel, index)) in coordinates
+            .iter_mut()
+            .zip(channels.iter_mut().zip([format.x, format.y]))
+        {
+            let &(mode, token) = values.get(index).ok_or(invalid.clone())?;
+            *coordinate = channel
+                .advance(mode, token, points.is_empty())
+                .ok_or(invalid.clone())?;
+        }
+        let [x, y] = coordinates;
+        points.push(Point { x, y });
+    }
+    Ok(points)
+}
+
+fn tokenize(values: &str) -> Option<Vec<(Option<ValueMode>, Token)>> {
+    let bytes = values.as_bytes();
+    let mut tokens = vec![];
+    let mut i = 0;
+    loop {
+        while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        if i == bytes.len() {
+            return Some(tokens);
+        }
+        let mode = match bytes[i] {
+            b'!' => Some(ValueMode::Explicit),
+            b'\'' => Some(ValueMode::FirstDifference),
+            b'"' => Some(ValueMode::SecondDifference),
+            _ => None,
+        };
+        if mode.is_some() {
+            i += 1;
+            while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+                i += 1;
+            }
+        }
+        let token = match bytes.get(i)? {
+            b'T' | b'F' => {
+                i += 1;
+                Token::Boolean
+            }
+            b'*' => {
+                i += 1;
+                Token::Unchanged
+            }
+            b'?' => {
+                i += 1;
+                Token::Unknown
+            }
+            _ => {
+                // numbers need no separator when the next one starts with a sign or a second
+                // decimal point, as in `10-5` or `1.5.5`
+                let start = i;
+                if matches!(bytes[i], b'-' | b'+') {
+                    i += 1;
+                }
+                let digits = i;
+                while i < bytes.len() && bytes[i].is_ascii_digit() {
+                    i += 1;
+                }
+                if i < bytes.len() && bytes[i] == b'.' {
+                    i += 1;
+                    while i < bytes.len() && bytes[i].is_ascii_digit() {
+                        i += 1;
+                    }
+                }
+                if i < bytes.len() && matches!(bytes[i], b'e' | b'E') {
+                    i += 1;
+                    if i < bytes.len() && matches!(bytes[i], b'-' | b'+') {
+                        i += 1;
+                    }
+                    while i < bytes.len() && bytes[i].is_ascii_digit() {
+                        i += 1;
+                    }
+                }
+                if !bytes[digits..i].iter().any(u8::is_ascii_digit) {
+                    return None;
+                }
+                Token::Number(values[start..i].parse().ok()?)
+            }
+        };
+        tokens.push((mode, token));
+    }
+}
+
+/// Writes strokes as an InkML document with `X` and `Y` channels, streaming one trace at a
+/// time.
+pub struct InkmlWriter<W: Write> {
+    inner: W,
+}
+
+impl<W: Write> InkmlWriter<W> {
+    /// Starts the document with its trace format.
+    pub fn new(mut inner: W) -> io::Result<Self> {
+        writeln!(inner, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(inner, r#"<ink xmlns="{}">"#, INKML_NAMESPACE)?;
+        writeln!(inner, "  <context>")?;
+        writeln!(inner, "    <traceFormat>")?;
+        writeln!(inner, r#"      <channel name="X" type="decimal"/>"#)?;
+        writeln!(inner, r#"      <channel name="Y" type="decimal"/>"#)?;
+        writeln!(inner, "    </traceFormat>")?;
+        writeln!(inner, "  </context>")?;
+        Ok(Self { inner })
+    }
+
+    /// Writes each stroke as a `<trace>` at the top level of the document.
+    pub fn write_strokes(&mut self, strokes: &[Stroke]) -> io::Result<()> {
+        for stroke in strokes {
+            self.write_trace(stroke, "  ")?;
+        }
+        Ok(())
+    }
+
+    /// Writes the strokes of one sample as a `<traceGroup>` annotated with `label` as its
+    /// `truth`, the way handwriting corpora label their samples.
+    pub fn write_labeled_strokes(&mut self, label: &str, strokes: &[Stroke]) -> io::Result<()> {
+        writeln!(self.inner, "  <traceGroup>")?;
+        writeln!(
+            self.inner,
+            r#"    <annotation type="truth">{}</annotation>"#,
+            escape_text(label)
+        )?;
+        for stroke in strokes {
+            self.write_trace(stroke, "    ")?;
+        }
+        writeln!(self.inner, "  </traceGroup>")?;
+        Ok(())
+    }
+
+    /// Closes the document and returns the underlying writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        writeln!(self.inner, "</ink>")?;
+        Ok(self.inner)
+    }
+
+    fn write_trace(&mut self, stroke: &Stroke, indent: &str) -> io::Result<()> {
+        write!(self.inner, "{}<trace>", indent)?;
+        for (i, p) in stroke.0.iter().enumerate() {
+            if i > 0 {
+                write!(self.inner, ", ")?;
+            }
+            write!(self.inner, "{} {}", p.x, p.y)?;
+        }
+        writeln!(self.inner, "</trace>")
+    }
+}
+
+/// Writes strokes as an InkML document, one `<trace>` per stroke.
+pub fn strokes_to_inkml(strokes: &[Stroke]) -> String {
+    let write = || -> io::Result<Vec<u8>> {
+        let mut writer = InkmlWriter::new(vec![])?;
+        writer.write_strokes(strokes)?;
+        writer.finish()
+    };
+    // writing to a Vec only fails on allocation failure, and the output is ASCII
+    String::from_utf8(write().unwrap()).unwrap()
+}
+
+fn escape_text(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn coordinates(strokes: &[Stroke]) -> Vec<Vec<(f64, f64)>> {
+        strokes
+            .iter()
+            .map(|stroke| stroke.0.iter().map(|p| (p.x, p.y)).collect())
+            .collect()
+    }
+
+    fn ink(body: &str) -> String {
+        format!(r#"<ink xmlns="{}">{}</ink>"#, INKML_NAMESPACE, body)
+    }
+
+    #[test]
+    fn explicit_values() {
+        let strokes = strokes_from_inkml(&ink("<trace>10 0, 9 14, 8 28</trace>")).unwrap();
+        assert_eq!(
+            coordinates(&strokes),
+            [[(10.0, 0.0), (9.0, 14.0), (8.0, 28.0)]]
+        );
+    }
+
+    #[test]
+    fn first_differences() {
+        // the qualifier holds for the following values of the channel, and numbers starting
+        // with a sign need no separator
+        let strokes = strokes_from_inkml(&ink("<trace>20 30, '2'-1, 4 3, !5 *</trace>")).unwrap();
+        assert_eq!(
+            coordinates(&strokes),
+            [[(20.0, 30.0), (22.0, 29.0), (26.0, 32.0), (5.0, 32.0)]]
+        );
+    }
+
+    #[test]
+    fn second_differences() {
+        let strokes = strokes_from_inkml(&ink(r#"<trace>0 0, '1'1, "1"0, 0 0</trace>"#)).unwrap();
+        assert_eq!(
+            coordinates(&strokes),
+            [[(0.0, 0.0), (1.0, 1.0), (3.0, 2.0), (5.0, 3.0)]]
+        );
+    }
+
+    #[test]
+    fn trace_format_channels() {
+        let strokes = strokes_from_inkml(&ink(r##"<traceFormat xml:id="tf">
+                 <channel name="T" type="integer"/>
+                 <channel name="Y" type="decimal"/>
+                 <channel name="X" type="decimal"/>
+               </traceFormat>
+               <context xml:id="c" traceFormatRef="#tf"/>
+               <traceGroup contextRef="#c">
+                 <trace>0 1 2, 10 3 4</trace>
+                 <trace type="penUp">20 5 6</trace>
+               </traceGroup>"##))
+        .unwrap();
+        assert_eq!(coordinates(&strokes), [[(2.0, 1.0), (4.0, 3.0)]]);
+    }
+
+    #[test]
+    fn invalid_points() {
+        assert_eq!(
+            strokes_from_inkml(&ink("<trace>0 0</trace><trace>1 1, 2</trace>")).unwrap_err(),
+            InkmlError::InvalidPoint { trace: 1, point: 1 }
+        );
+        // there is no previous value to keep on the first point
+        assert_eq!(
+            strokes_from_inkml(&ink("<trace>* 0</trace>")).unwrap_err(),
+            InkmlError::InvalidPoint { trace: 0, point: 0 }
+        );
+        assert_eq!(
+            strokes_from_inkml(&ink(r##"<trace contextRef="#missing">0 0</trace>"##)).unwrap_err(),
+            InkmlError::UnresolvedReference {
+                reference: "#missing".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let strokes = vec![
+            Stroke(vec![(0.5, 1.0).into(), (-2.25, 3e-3).into()]),
+            Stroke(vec![
+                (100.0, 200.0).into(),
+                (150.0, 180.0).into(),
+                (1e6, 0.1).into(),
+            ]),
+        ];
+        let xml = strokes_to_inkml(&strokes);
+        assert_eq!(
+            coordinates(&strokes_from_inkml(&xml).unwrap()),
+            coordinates(&strokes)
+        );
+    }
+}
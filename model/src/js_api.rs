@@ -0,0 +1,333 @@
+//! Typed wasm API taking JS values. The TypeScript definitions of its types are generated along
+//! with the bindings.
+
+use serde::{Deserialize, Serialize};
+use tsify::Tsify;
+use wasm_bindgen::prelude::*;
+
+#[cfg(feature = "svg")]
+use crate::svg::SvgError;
+use crate::{
+    AugmentParams, CleanupParams, FeatureAccumulator, FeatureModel,
+    canvas::CanvasSpec,
+    cleanup::cleanup_strokes,
+    error::ModelError,
+    explain::{CellDifference, Explanation},
+    extractor::get_extractor,
+    stroke::{PenPoint, PenStroke, Stroke, check_strokes},
+    try_coords_to_strokes, try_strokes_flattened_to_strokes,
+};
+
+/// Strokes as arrays of points.
+#[derive(Debug, Clone, Deserialize, Tsify)]
+#[tsify(from_wasm_abi)]
+pub struct StrokesInput(pub Vec<Vec<PointInput>>);
+
+/// A point as `[x, y]`, or as an object with the pen data of the pointer event: `pressure`
+/// from 0 to 1 and `time` in milliseconds (see [`PenPoint`]). Leave out the pressure of
+/// devices that do not sense it, such as mice.
+#[derive(Debug, Clone, Deserialize, Tsify)]
+#[serde(untagged)]
+pub enum PointInput {
+    Position([f64; 2]),
+    Pen {
+        x: f64,
+        y: f64,
+        #[serde(default)]
+        #[tsify(optional)]
+        pressure: Option<f64>,
+        #[serde(default)]
+        #[tsify(optional)]
+        time: Option<f64>,
+    },
+}
+
+impl From<PointInput> for PenPoint {
+    fn from(point: PointInput) -> Self {
+        match point {
+            PointInput::Position([x, y]) => PenPoint {
+                x,
+                y,
+                pressure: None,
+                time: None,
+            },
+            PointInput::Pen {
+                x,
+                y,
+                pressure,
+                time,
+            } => PenPoint {
+                x,
+                y,
+                pressure,
+                time,
+            },
+        }
+    }
+}
+
+impl StrokesInput {
+    fn into_pen_strokes(self) -> Vec<PenStroke> {
+        self.0
+            .into_iter()
+            .map(|points| PenStroke(points.into_iter().map(PenPoint::from).collect()))
+            .collect()
+    }
+
+    /// The strokes without the pen data.
+    fn into_strokes(self) -> Vec<Stroke> {
+        self.into_pen_strokes().iter().map(Stroke::from).collect()
+    }
+}
+
+/// Strokes as arrays of `[x, y]` points.
+#[derive(Debug, Clone, Serialize, Tsify)]
+#[tsify(into_wasm_abi)]
+pub struct StrokesOutput(pub Vec<Vec<[f64; 2]>>);
+
+impl From<&[Stroke]> for StrokesOutput {
+    fn from(strokes: &[Stroke]) -> Self {
+        StrokesOutput(
+            strokes
+                .iter()
+                .map(|stroke| stroke.0.iter().map(|p| [p.x, p.y]).collect())
+                .collect(),
+        )
+    }
+}
+
+/// One feature per augmented copy of a query.
+#[derive(Debug, Clone, Serialize, Tsify)]
+#[tsify(into_wasm_abi)]
+pub struct FeatureArrays(pub Vec<Vec<f64>>);
+
+/// The cells that differ the most between two features (see
+/// [`crate::FeatureExtractor::compare_feature_arrays`]).
+#[derive(Debug, Clone, Serialize, Tsify)]
+#[tsify(into_wasm_abi)]
+pub struct CellDifferences(pub Vec<CellDifference>);
+
+/// The canvas the strokes are drawn on, as a plain object (see [`CanvasSpec`]). Every function
+/// of the wasm API taking a canvas takes it in this form.
+#[derive(Debug, Clone, Serialize, Deserialize, Tsify)]
+#[tsify(from_wasm_abi)]
+pub struct CanvasInput {
+    pub width: f64,
+    pub height: f64,
+    #[serde(default)]
+    #[tsify(optional)]
+    pub origin_x: f64,
+    #[serde(default)]
+    #[tsify(optional)]
+    pub origin_y: f64,
+}
+
+impl From<CanvasSpec> for CanvasInput {
+    fn from(canvas: CanvasSpec) -> Self {
+        CanvasInput {
+            width: canvas.width,
+            height: canvas.height,
+            origin_x: canvas.origin_x,
+            origin_y: canvas.origin_y,
+        }
+    }
+}
+
+impl From<CanvasInput> for CanvasSpec {
+    fn from(canvas: CanvasInput) -> Self {
+        CanvasSpec {
+            width: canvas.width,
+            height: canvas.height,
+            origin_x: canvas.origin_x,
+            origin_y: canvas.origin_y,
+        }
+    }
+}
+
+/// Thrown as an `Error` named `ModelError`, whose `cause` is the [`ModelError`] object.
+impl From<ModelError> for JsValue {
+    fn from(err: ModelError) -> Self {
+        let error = js_sys::Error::new(&err.to_string());
+        error.set_name("ModelError");
+        if let Ok(cause) = serde_wasm_bindgen::to_value(&err) {
+            error.set_cause(&cause);
+        }
+        error.into()
+    }
+}
+
+/// Strokes read from an SVG document, on the canvas of the document. Both can be passed on to
+/// `FeatureModel.strokes_to_feature_array`.
+#[cfg(feature = "svg")]
+#[derive(Debug, Clone, Serialize, Tsify)]
+#[tsify(into_wasm_abi)]
+pub struct SvgDrawing {
+    pub canvas: CanvasInput,
+    pub strokes: Vec<Vec<[f64; 2]>>,
+}
+
+/// Thrown as an `Error` named `SvgError`.
+#[cfg(feature = "svg")]
+impl From<SvgError> for JsValue {
+    fn from(err: SvgError) -> Self {
+        let error = js_sys::Error::new(&err.to_string());
+        error.set_name("SvgError");
+        error.into()
+    }
+}
+
+/// Reads the strokes of an SVG document (see [`crate::drawing_from_svg`]). Throws an
+/// `SvgError` if the document cannot be read.
+#[cfg(feature = "svg")]
+#[wasm_bindgen(js_name = drawing_from_svg)]
+pub fn svg_drawing(svg: &str) -> Result<SvgDrawing, JsValue> {
+    let drawing = crate::svg::drawing_from_svg(svg)?;
+    Ok(SvgDrawing {
+        canvas: drawing.canvas.into(),
+        strokes: drawing
+            .strokes
+            .iter()
+            .map(|stroke| stroke.0.iter().map(|p| [p.x, p.y]).collect())
+            .collect(),
+    })
+}
+
+/// Cleans up strokes given as arrays of points (the pen data is ignored) with
+/// [`CleanupParams::DEFAULT`], for display. The strokes are drawn on `canvas` (GlyphWiki's
+/// 200x200 frame if omitted), and so is the result. Throws a `ModelError` on invalid input.
+#[wasm_bindgen(js_name = cleanup_strokes)]
+pub fn cleanup_input_strokes(
+    strokes: StrokesInput,
+    canvas: Option<CanvasInput>,
+) -> Result<StrokesOutput, JsValue> {
+    let strokes = strokes.into_strokes();
+    let canvas = canvas.map_or(CanvasSpec::GLYPHWIKI, CanvasSpec::from);
+    canvas.check()?;
+    check_strokes(&strokes)?;
+    let cleaned = cleanup_strokes(
+        &canvas.strokes_to_glyphwiki(&strokes),
+        &CleanupParams::DEFAULT,
+    );
+    Ok(canvas.strokes_from_glyphwiki(&cleaned)[..].into())
+}
+
+#[wasm_bindgen]
+impl FeatureAccumulator {
+    /// Creates an accumulator for strokes drawn on `canvas` (GlyphWiki's 200x200 frame if
+    /// omitted). Throws a `ModelError` on an invalid canvas.
+    #[wasm_bindgen(constructor)]
+    pub fn for_version(
+        version: &str,
+        canvas: Option<CanvasInput>,
+    ) -> Result<FeatureAccumulator, JsValue> {
+        let extractor = get_extractor(version)
+            .ok_or_else(|| JsError::new(&format!("unknown model version: {}", version)))?;
+        let canvas = canvas.map_or(CanvasSpec::GLYPHWIKI, CanvasSpec::from);
+        Ok(FeatureAccumulator::new(extractor, canvas)?)
+    }
+}
+
+#[wasm_bindgen]
+impl FeatureModel {
+    /// Computes the feature of strokes given as arrays of points, drawn on `canvas`
+    /// (GlyphWiki's 200x200 frame if omitted). The pen data of the points, if any, is used by
+    /// the preprocessing of the model. Throws a `ModelError` on invalid input.
+    pub fn strokes_to_feature_array(
+        &self,
+        strokes: StrokesInput,
+        canvas: Option<CanvasInput>,
+    ) -> Result<Box<[f64]>, JsValue> {
+        let strokes = strokes.into_pen_strokes();
+        let canvas = canvas.map_or(CanvasSpec::GLYPHWIKI, CanvasSpec::from);
+        Ok(self
+            .extractor
+            .try_pen_strokes_to_feature_array_on_canvas(&strokes, &canvas)?
+            .into())
+    }
+
+    /// Computes the feature of strokes in the layout of `strokes_flattened_to_feature_array`,
+    /// drawn on `canvas`. Throws a `ModelError` on invalid input.
+    pub fn strokes_flattened_to_feature_array_on_canvas(
+        &self,
+        strokes_flattened: &[i32],
+        canvas: CanvasInput,
+    ) -> Result<Box<[f64]>, JsValue> {
+        let strokes = try_strokes_flattened_to_strokes(strokes_flattened)?;
+        self.feature_array_on_canvas(&strokes, Some(canvas))
+    }
+
+    /// Computes the feature of strokes given as interleaved `coords` (x0, y0, x1, y1, ...) and
+    /// the index of the first point of each stroke in `offsets`, drawn on `canvas` (GlyphWiki's
+    /// 200x200 frame if omitted). Throws a `ModelError` on invalid input.
+    pub fn coords_to_feature_array(
+        &self,
+        coords: &[f64],
+        offsets: &[u32],
+        canvas: Option<CanvasInput>,
+    ) -> Result<Box<[f64]>, JsValue> {
+        let strokes = try_coords_to_strokes(coords, offsets)?;
+        self.feature_array_on_canvas(&strokes, canvas)
+    }
+
+    /// The feature of strokes given as arrays of points (the pen data is ignored), along with
+    /// the elements it was rendered from and the `top_cells` cells each contributes to the
+    /// most, for debugging. `undefined` if the model cannot explain its features. Throws a
+    /// `ModelError` on invalid input.
+    pub fn explain_feature_array(
+        &self,
+        strokes: StrokesInput,
+        top_cells: usize,
+        canvas: Option<CanvasInput>,
+    ) -> Result<Option<Explanation>, JsValue> {
+        let strokes = strokes.into_strokes();
+        let canvas = canvas.map_or(CanvasSpec::GLYPHWIKI, CanvasSpec::from);
+        canvas.check()?;
+        check_strokes(&strokes)?;
+        Ok(self
+            .extractor
+            .explain_feature_array(&canvas.strokes_to_glyphwiki(&strokes), top_cells))
+    }
+
+    /// The features of `n` perturbed copies of strokes given as arrays of points (the pen data
+    /// is ignored), drawn on `canvas` (GlyphWiki's 200x200 frame if omitted), with the default
+    /// perturbation ranges. The same `seed` always gives the same copies. Throws a `ModelError`
+    /// on invalid input.
+    pub fn augmented_feature_arrays(
+        &self,
+        strokes: StrokesInput,
+        n: usize,
+        seed: u32,
+        canvas: Option<CanvasInput>,
+    ) -> Result<FeatureArrays, JsValue> {
+        let strokes = strokes.into_strokes();
+        let canvas = canvas.map_or(CanvasSpec::GLYPHWIKI, CanvasSpec::from);
+        canvas.check()?;
+        check_strokes(&strokes)?;
+        Ok(FeatureArrays(self.extractor.try_augmented_feature_arrays(
+            &canvas.strokes_to_glyphwiki(&strokes),
+            &AugmentParams::DEFAULT,
+            n,
+            seed.into(),
+        )?))
+    }
+
+    /// The `top` cells accounting for most of the distance between two features of this model,
+    /// largest first, with what they stand for.
+    pub fn compare_feature_arrays(&self, a: &[f64], b: &[f64], top: usize) -> CellDifferences {
+        CellDifferences(self.extractor.compare_feature_arrays(a, b, top))
+    }
+}
+
+impl FeatureModel {
+    fn feature_array_on_canvas(
+        &self,
+        strokes: &[Stroke],
+        canvas: Option<CanvasInput>,
+    ) -> Result<Box<[f64]>, JsValue> {
+        let canvas = canvas.map_or(CanvasSpec::GLYPHWIKI, CanvasSpec::from);
+        Ok(self
+            .extractor
+            .try_strokes_to_feature_array_on_canvas(strokes, &canvas)?
+            .into())
+    }
+}
@@ -1,42 +1,399 @@
 use wasm_bindgen::prelude::*;
 
-pub use crate::model::{FEATURE_COLSIZE, MODEL_VERSION, strokes_to_feature_array};
-pub use crate::stroke::{Point, Stroke};
+pub use crate::accumulator::FeatureAccumulator;
+pub use crate::augment::AugmentParams;
+pub use crate::canvas::CanvasSpec;
+pub use crate::cleanup::{CleanupParams, cleanup_pen_strokes, cleanup_strokes};
+pub use crate::curve::{cubic_bezier_stroke, quadratic_bezier_stroke};
+pub use crate::def::{DefModel, DefParams};
+pub use crate::drawing::Drawing;
+pub use crate::error::ModelError;
+pub use crate::explain::{
+    CellContribution, CellDifference, CellLabel, ElementExplanation, Explanation, FeatureBlock,
+    SegmentRole, compare_feature_arrays,
+};
+pub use crate::extractor::{
+    DEFAULT_MODEL_VERSION, ExtractorParams, FeatureExtractor, default_extractor, extractors,
+    get_extractor,
+};
+pub use crate::fusion::{FusedResults, Fusion};
+pub use crate::global_feature::GlobalFeatureParams;
+#[cfg(feature = "inkml")]
+pub use crate::inkml::{
+    INKML_NAMESPACE, InkmlError, InkmlWriter, strokes_from_inkml, strokes_to_inkml,
+};
+#[cfg(feature = "js")]
+pub use crate::js_api::{
+    CanvasInput, CellDifferences, FeatureArrays, PointInput, StrokesInput, StrokesOutput,
+    cleanup_input_strokes,
+};
+#[cfg(all(feature = "js", feature = "svg"))]
+pub use crate::js_api::{SvgDrawing, svg_drawing};
+pub use crate::model::SegmentModel;
+pub use crate::normalize::{Normalization, normalize_strokes};
+pub use crate::params::{
+    AbsGridLevel, AbsGridParams, AngleAxis, ModelParams, RelGridLevel, RelGridParams,
+    SegmentDirection, SummaryMode, SummaryParams,
+};
+pub use crate::quantize::{Quantization, QuantizedFeature, QuantizedFormat};
+pub use crate::segmentation::{SegmentationParams, segment_pen_strokes, segment_strokes};
+pub use crate::stroke::{
+    ChordDistance, PenPoint, PenStroke, Point, Stroke, check_pen_strokes, check_strokes,
+};
+pub use crate::stroke_relation::StrokeRelationParams;
+pub use crate::stroke_type::{StrokeType, StrokeTypeParams};
+#[cfg(feature = "svg")]
+pub use crate::svg::{SVG_NAMESPACE, SvgError, drawing_from_svg, strokes_from_svg};
 
+mod accumulator;
+mod augment;
+mod canvas;
+mod cleanup;
+mod curve;
+mod def;
+mod drawing;
+mod error;
+mod explain;
+mod extractor;
+mod fusion;
+mod geometry;
+mod global_feature;
 mod indexed_feature;
+#[cfg(feature = "inkml")]
+mod inkml;
+#[cfg(feature = "js")]
+mod js_api;
 mod model;
+mod normalize;
+mod params;
+mod quantize;
+mod segmentation;
+mod simplify;
 mod stroke;
+mod stroke_relation;
+mod stroke_type;
+#[cfg(feature = "svg")]
+mod svg;
 
 #[wasm_bindgen]
 pub fn model_version() -> String {
-    MODEL_VERSION.to_string()
+    default_extractor().model_version().to_string()
 }
 
 #[wasm_bindgen]
 pub fn feature_colsize() -> usize {
-    FEATURE_COLSIZE
+    default_extractor().feature_colsize()
 }
 
 #[wasm_bindgen]
-pub fn strokes_flattened_to_feature_array(strokes_flattened: &[i32]) -> Box<[f64]> {
-    // `strokes_flattened` is a flattened array of strokes, where each stroke is a sequence of
-    // (x, y) coordinates preceded by the number of points in the stroke. The first element of
-    // `strokes_flattened` is the number of strokes.
+pub fn model_versions() -> Vec<String> {
+    extractors()
+        .map(|extractor| extractor.model_version().to_string())
+        .collect()
+}
+
+#[wasm_bindgen]
+pub fn strokes_flattened_to_feature_array(
+    strokes_flattened: &[i32],
+) -> Result<Box<[f64]>, JsError> {
+    let strokes = try_strokes_flattened_to_strokes(strokes_flattened)?;
+    Ok(default_extractor()
+        .try_strokes_to_feature_array(&strokes)?
+        .into())
+}
+
+/// Merges the kNN results of several queries.
+///
+/// The lists are given concatenated: `names` and `distances` hold all the results, and
+/// `list_lengths` the number of results of each list. `method` is `"min_distance"` or
+/// `"reciprocal_rank"`.
+#[wasm_bindgen]
+pub fn fuse_search_results(
+    names: Vec<String>,
+    distances: &[f64],
+    list_lengths: &[u32],
+    method: &str,
+) -> Result<FusedResults, JsError> {
+    let fusion = match method {
+        "min_distance" => Fusion::MinDistance,
+        "reciprocal_rank" => Fusion::RECIPROCAL_RANK,
+        _ => return Err(JsError::new(&format!("unknown fusion method: {}", method))),
+    };
+    let total: usize = list_lengths.iter().map(|&len| len as usize).sum();
+    if names.len() != total || distances.len() != total {
+        return Err(JsError::new(
+            "names and distances do not match the list lengths",
+        ));
+    }
+    let mut results = names.into_iter().zip(distances.iter().copied());
+    let lists: Vec<Vec<_>> = list_lengths
+        .iter()
+        .map(|&len| results.by_ref().take(len as usize).collect())
+        .collect();
+    Ok(FusedResults::new(fusion.fuse(&lists)))
+}
+
+/// A feature extractor of a specific model version.
+#[wasm_bindgen]
+pub struct FeatureModel {
+    extractor: &'static dyn FeatureExtractor,
+}
+
+#[wasm_bindgen]
+impl FeatureModel {
+    #[wasm_bindgen(constructor)]
+    pub fn new(version: &str) -> Result<FeatureModel, JsError> {
+        let extractor = get_extractor(version)
+            .ok_or_else(|| JsError::new(&format!("unknown model version: {}", version)))?;
+        Ok(FeatureModel { extractor })
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn version(&self) -> String {
+        self.extractor.model_version().to_string()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn colsize(&self) -> usize {
+        self.extractor.feature_colsize()
+    }
+
+    pub fn strokes_flattened_to_feature_array(
+        &self,
+        strokes_flattened: &[i32],
+    ) -> Result<Box<[f64]>, JsError> {
+        let strokes = try_strokes_flattened_to_strokes(strokes_flattened)?;
+        Ok(self
+            .extractor
+            .try_strokes_to_feature_array(&strokes)?
+            .into())
+    }
+
+    /// The default quantization of the model. Queries to a quantized index must be quantized
+    /// with the mapping recorded in its metadata instead.
+    #[wasm_bindgen(getter)]
+    pub fn quantization(&self) -> Quantization {
+        self.extractor.quantization()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn quantization_scale(&self) -> f64 {
+        self.extractor.quantization().scale
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn quantization_offset(&self) -> f64 {
+        self.extractor.quantization().offset
+    }
+
+    pub fn quantize_u8(&self, feature: &[f64]) -> Box<[u8]> {
+        self.extractor.quantization().quantize_u8(feature).into()
+    }
 
+    pub fn dequantize_u8(&self, codes: &[u8]) -> Box<[f64]> {
+        self.extractor.quantization().dequantize_u8(codes).into()
+    }
+
+    pub fn quantize_f16(&self, feature: &[f64]) -> Box<[u16]> {
+        self.extractor.quantization().quantize_f16(feature).into()
+    }
+
+    pub fn dequantize_f16(&self, codes: &[u16]) -> Box<[f64]> {
+        self.extractor.quantization().dequantize_f16(codes).into()
+    }
+}
+
+/// Decodes strokes from interleaved coordinates.
+///
+/// `coords` holds the (x, y) coordinates of all the points, and `offsets` the index of the
+/// first point of each stroke; each stroke runs up to the start of the next one, the last one
+/// up to the end of `coords`.
+pub fn try_coords_to_strokes(coords: &[f64], offsets: &[u32]) -> Result<Vec<Stroke>, ModelError> {
+    if !coords.len().is_multiple_of(2) {
+        return Err(ModelError::OddCoordinateCount { len: coords.len() });
+    }
+    let n_points = coords.len() / 2;
+    let mut previous = 0;
+    for (stroke, &offset) in offsets.iter().enumerate() {
+        let offset = offset as usize;
+        if offset < previous || offset > n_points || (stroke == 0 && offset != 0) {
+            return Err(ModelError::InvalidOffset { stroke, offset });
+        }
+        previous = offset;
+    }
+
+    let mut strokes = Vec::with_capacity(offsets.len());
+    for (stroke, &start) in offsets.iter().enumerate() {
+        let end = offsets
+            .get(stroke + 1)
+            .map_or(n_points, |&next| next as usize);
+        let points = coords[2 * start as usize..2 * end]
+            .chunks_exact(2)
+            .map(|c| Point { x: c[0], y: c[1] })
+            .collect();
+        strokes.push(Stroke(points));
+    }
+    Ok(strokes)
+}
+
+/// Decodes strokes from a flattened array.
+///
+/// `strokes_flattened` is a flattened array of strokes, where each stroke is a sequence of
+/// (x, y) coordinates preceded by the number of points in the stroke. The first element of
+/// `strokes_flattened` is the number of strokes.
+pub fn try_strokes_flattened_to_strokes(
+    strokes_flattened: &[i32],
+) -> Result<Vec<Stroke>, ModelError> {
+    let read = |offset: usize| {
+        strokes_flattened
+            .get(offset)
+            .copied()
+            .ok_or(ModelError::UnexpectedEnd { offset })
+    };
+    let read_count = |offset: usize| {
+        let count = read(offset)?;
+        usize::try_from(count).map_err(|_| ModelError::InvalidCount { offset, count })
+    };
+
+    let n_strokes = read_count(0)?;
     let mut strokes = Vec::new();
     let mut i = 1;
-    for _ in 0..strokes_flattened[0] {
-        let n_points = strokes_flattened[i] as usize;
+    for _ in 0..n_strokes {
+        let n_points = read_count(i)?;
         let mut points = Vec::new();
         for j in 0..n_points {
             points.push(Point {
-                x: strokes_flattened[i + 1 + 2 * j] as f64,
-                y: strokes_flattened[i + 1 + 2 * j + 1] as f64,
+                x: read(i + 1 + 2 * j)? as f64,
+                y: read(i + 1 + 2 * j + 1)? as f64,
             });
         }
         strokes.push(Stroke(points));
         i += 1 + 2 * n_points;
     }
+    Ok(strokes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    strokes_to_feature_array(&strokes).into()
+    fn coordinates(strokes: &[Stroke]) -> Vec<Vec<(f64, f64)>> {
+        strokes
+            .iter()
+            .map(|stroke| stroke.0.iter().map(|p| (p.x, p.y)).collect())
+            .collect()
+    }
+
+    #[test]
+    fn flattened_strokes() {
+        let strokes = try_strokes_flattened_to_strokes(&[2, 2, 0, 0, 10, 10, 1, 5, 6]).unwrap();
+        assert_eq!(
+            coordinates(&strokes),
+            [vec![(0.0, 0.0), (10.0, 10.0)], vec![(5.0, 6.0)]]
+        );
+        assert!(
+            default_extractor()
+                .try_strokes_to_feature_array(&strokes)
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn flattened_strokes_errors() {
+        // the y of the last point is missing
+        assert_eq!(
+            try_strokes_flattened_to_strokes(&[2, 2, 0, 0, 10, 10, 1, 5]).unwrap_err(),
+            ModelError::UnexpectedEnd { offset: 8 }
+        );
+        // the point count of the second stroke is missing
+        assert_eq!(
+            try_strokes_flattened_to_strokes(&[2, 1, 0, 0]).unwrap_err(),
+            ModelError::UnexpectedEnd { offset: 4 }
+        );
+        assert_eq!(
+            try_strokes_flattened_to_strokes(&[]).unwrap_err(),
+            ModelError::UnexpectedEnd { offset: 0 }
+        );
+        assert_eq!(
+            try_strokes_flattened_to_strokes(&[-1]).unwrap_err(),
+            ModelError::InvalidCount {
+                offset: 0,
+                count: -1
+            }
+        );
+        assert_eq!(
+            try_strokes_flattened_to_strokes(&[2, 1, 0, 0, -3]).unwrap_err(),
+            ModelError::InvalidCount {
+                offset: 4,
+                count: -3
+            }
+        );
+        // a stroke without points is decoded, and rejected by the feature extraction
+        let strokes = try_strokes_flattened_to_strokes(&[2, 1, 0, 0, 0]).unwrap();
+        assert_eq!(
+            default_extractor()
+                .try_strokes_to_feature_array(&strokes)
+                .unwrap_err(),
+            ModelError::EmptyStroke { stroke: 1 }
+        );
+    }
+
+    #[test]
+    fn coords_strokes() {
+        let coords = [0.5, 0.0, 10.0, 10.0, 5.0, 6.25];
+        assert_eq!(
+            coordinates(&try_coords_to_strokes(&coords, &[0, 2]).unwrap()),
+            [vec![(0.5, 0.0), (10.0, 10.0)], vec![(5.0, 6.25)]]
+        );
+        assert!(try_coords_to_strokes(&[], &[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn coords_strokes_errors() {
+        let coords = [0.0, 0.0, 10.0, 10.0, 5.0, 6.0];
+        assert_eq!(
+            try_coords_to_strokes(&coords[..5], &[0]).unwrap_err(),
+            ModelError::OddCoordinateCount { len: 5 }
+        );
+        // the first stroke must start at the first point
+        assert_eq!(
+            try_coords_to_strokes(&coords, &[1]).unwrap_err(),
+            ModelError::InvalidOffset {
+                stroke: 0,
+                offset: 1
+            }
+        );
+        assert_eq!(
+            try_coords_to_strokes(&coords, &[0, 2, 1]).unwrap_err(),
+            ModelError::InvalidOffset {
+                stroke: 2,
+                offset: 1
+            }
+        );
+        assert_eq!(
+            try_coords_to_strokes(&coords, &[0, 4]).unwrap_err(),
+            ModelError::InvalidOffset {
+                stroke: 1,
+                offset: 4
+            }
+        );
+        // a stroke starting at the end has no points
+        let strokes = try_coords_to_strokes(&coords, &[0, 3]).unwrap();
+        assert_eq!(
+            default_extractor()
+                .try_strokes_to_feature_array(&strokes)
+                .unwrap_err(),
+            ModelError::EmptyStroke { stroke: 1 }
+        );
+        let strokes = try_coords_to_strokes(&[0.0, 0.0, f64::NAN, 1.0], &[0]).unwrap();
+        assert_eq!(
+            default_extractor()
+                .try_strokes_to_feature_array(&strokes)
+                .unwrap_err(),
+            ModelError::NonFiniteCoordinate {
+                stroke: 0,
+                point: 1
+            }
+        );
+    }
 }
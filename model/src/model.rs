@@ -1,94 +1,595 @@
+use std::borrow::Cow;
+
 use crate::{
-    indexed_feature::{Feature, IndexedFeatureDim, IndexedFeatureElement},
-    stroke::{Point, Stroke},
+    canvas::CanvasSpec,
+    cleanup::{cleanup_pen_strokes, cleanup_strokes},
+    explain::{
+        CellContribution, CellLabel, ElementExplanation, Explanation, FeatureBlock, SegmentRole,
+    },
+    extractor::{ExtractorParams, FeatureExtractor},
+    indexed_feature::{Feature, IndexedFeatureDim, IndexedFeatureElement, Pyramid},
+    normalize::{Normalization, normalize_strokes},
+    params::{
+        AbsGridParams, AngleAxis, ModelParams, RelGridParams, SegmentDirection, SummaryMode,
+        SummaryParams,
+    },
+    quantize::Quantization,
+    segmentation::{segment_pen_strokes, segment_strokes},
+    stroke::{PenStroke, Point, Stroke},
 };
 
-pub const MODEL_VERSION: &str = "2";
-
-const PARAM_N_PT_X: usize = 2;
-const PARAM_N_PT_Y: usize = 2;
-const PARAM_N_SEG_X: usize = 3;
-const PARAM_N_SEG_Y: usize = 3;
-const PARAM_N_SEG_MAG: usize = 6;
-const PARAM_N_SEG_ANGLE: usize = 7;
+/// Size of the frame the strokes are given in (see [`CanvasSpec`]).
+const FRAME_SIZE: f64 = CanvasSpec::GLYPHWIKI.width;
+/// Segment length mapped to the last cell of the magnitude axis.
+const MAG_RANGE: f64 = 1.25 * FRAME_SIZE;
 
 const ABS_FEATURE_DIM: usize = 4;
-struct AbsFeatureDim {}
+struct AbsFeatureDim {
+    n_x: usize,
+    n_y: usize,
+}
 impl IndexedFeatureDim<ABS_FEATURE_DIM> for AbsFeatureDim {
-    const DIM: [usize; ABS_FEATURE_DIM] = [PARAM_N_PT_X, PARAM_N_PT_Y, PARAM_N_PT_X, PARAM_N_PT_Y];
+    fn dim(&self) -> [usize; ABS_FEATURE_DIM] {
+        let AbsFeatureDim { n_x, n_y } = *self;
+        [n_x, n_y, n_x, n_y]
+    }
+}
+
+/// The main grid followed by the extra levels.
+fn abs_feature_pyramid(params: &AbsGridParams) -> Pyramid<AbsFeatureDim> {
+    let main = AbsFeatureDim {
+        n_x: params.n_x,
+        n_y: params.n_y,
+    };
+    let extra = params.extra_levels.iter().map(|level| {
+        let dim = AbsFeatureDim {
+            n_x: level.n_x,
+            n_y: level.n_y,
+        };
+        (dim, level.weight)
+    });
+    Pyramid {
+        levels: std::iter::once((main, 1.0)).chain(extra).collect(),
+    }
 }
 type AbsFeatureElement = IndexedFeatureElement<ABS_FEATURE_DIM>;
 
+/// A block computed from all the strokes together and appended after the Gaussian blocks. The
+/// `weight` of its parameters scales the whole block relative to the Gaussian blocks.
+pub(crate) trait StrokeBlock {
+    fn colsize(&self) -> usize;
+
+    /// Strokes are in the [`CanvasSpec::GLYPHWIKI`] frame and must have been checked with
+    /// [`crate::check_strokes`].
+    fn generate_feature_array(&self, strokes: &[Stroke]) -> Vec<f64>;
+}
+
 fn calc_abs_index((p, q): (&Point, &Point), k: f64) -> AbsFeatureElement {
-    let index = [p.x / 200.0, p.y / 200.0, q.x / 200.0, q.y / 200.0];
+    let index = [
+        p.x / FRAME_SIZE,
+        p.y / FRAME_SIZE,
+        q.x / FRAME_SIZE,
+        q.y / FRAME_SIZE,
+    ];
     let value = k;
     AbsFeatureElement { index, value }
 }
 
 const REL_FEATURE_DIM: usize = 4;
-struct RelFeatureDim {}
+struct RelFeatureDim {
+    dim: [usize; REL_FEATURE_DIM],
+    angle_axis: AngleAxis,
+}
 impl IndexedFeatureDim<REL_FEATURE_DIM> for RelFeatureDim {
-    const DIM: [usize; REL_FEATURE_DIM] = [
-        PARAM_N_SEG_X,
-        PARAM_N_SEG_Y,
-        PARAM_N_SEG_MAG,
-        PARAM_N_SEG_ANGLE,
-    ];
+    fn dim(&self) -> [usize; REL_FEATURE_DIM] {
+        self.dim
+    }
+
+    fn periodic(&self) -> [bool; REL_FEATURE_DIM] {
+        [false, false, false, self.angle_axis == AngleAxis::Periodic]
+    }
+}
+
+/// The main grid followed by the extra levels.
+fn rel_feature_pyramid(params: &RelGridParams) -> Pyramid<RelFeatureDim> {
+    let main = RelFeatureDim {
+        dim: [params.n_x, params.n_y, params.n_mag, params.n_angle],
+        angle_axis: params.angle_axis,
+    };
+    let extra = params.extra_levels.iter().map(|level| {
+        let dim = RelFeatureDim {
+            dim: [level.n_x, level.n_y, level.n_mag, level.n_angle],
+            angle_axis: params.angle_axis,
+        };
+        (dim, level.weight)
+    });
+    Pyramid {
+        levels: std::iter::once((main, 1.0)).chain(extra).collect(),
+    }
 }
 type RelFeatureElement = IndexedFeatureElement<REL_FEATURE_DIM>;
 
-fn calc_rel_index((p, q): (&Point, &Point), k: f64) -> RelFeatureElement {
+fn calc_rel_index(
+    (p, q): (&Point, &Point),
+    k: f64,
+    params: &RelGridParams,
+    undirected: bool,
+) -> RelFeatureElement {
     let dx = q.x - p.x;
     let dy = q.y - p.y;
     let mag = dx.hypot(dy);
     let angle = dx.atan2(dy); // upward segments have angle = pi or -pi
 
+    let angle_index = match (params.angle_axis, undirected) {
+        (AngleAxis::Clamped, false) => (angle / std::f64::consts::PI + 0.5) / 1.5,
+        (AngleAxis::Clamped, true) => angle.rem_euclid(std::f64::consts::PI) / std::f64::consts::PI,
+        (AngleAxis::Periodic, false) => angle / std::f64::consts::TAU,
+        (AngleAxis::Periodic, true) => angle / std::f64::consts::PI,
+    };
     let index = [
-        (p.x + q.x) / 400.0,
-        (p.y + q.y) / 400.0,
-        mag / 250.0,
-        (angle / std::f64::consts::PI + 0.5) / 1.5,
+        (p.x + q.x) / (2.0 * FRAME_SIZE),
+        (p.y + q.y) / (2.0 * FRAME_SIZE),
+        mag / MAG_RANGE,
+        angle_index,
     ];
-    let value = k * (0.5 + mag / 400.0) * 1.3;
+    let value = k * (0.5 + mag / params.mag_weight_scale) * params.gain;
     RelFeatureElement { index, value }
 }
 
-pub const FEATURE_COLSIZE: usize = AbsFeatureDim::COLSIZE + RelFeatureDim::COLSIZE;
+/// The segment-endpoint Gaussian model: every stroke is (optionally normalized and) summarized
+/// into a few points and the segments between them are rendered onto the absolute and relative
+/// grids.
+#[derive(Debug, Clone)]
+pub struct SegmentModel {
+    version: Cow<'static, str>,
+    params: ModelParams,
+}
+
+impl SegmentModel {
+    pub const fn new(version: Cow<'static, str>, params: ModelParams) -> Self {
+        SegmentModel { version, params }
+    }
+
+    pub fn params(&self) -> &ModelParams {
+        &self.params
+    }
+
+    /// Whether the feature is the sum of per-stroke features. Segmentation, normalization, the
+    /// global descriptors and the stroke relations depend on all the strokes together, so such
+    /// a model is not additive.
+    fn is_additive(&self) -> bool {
+        self.params.segmentation.is_none()
+            && self.params.normalization == Normalization::None
+            && self.params.global.is_none()
+            && self.params.stroke_relations.is_none()
+    }
+}
+
+/// The registered versions of the segment model, oldest first.
+pub(crate) static SEGMENT_MODELS: [SegmentModel; 14] = [
+    SegmentModel::new(Cow::Borrowed("2"), ModelParams::V2),
+    SegmentModel::new(Cow::Borrowed("3"), ModelParams::V3),
+    SegmentModel::new(Cow::Borrowed("4"), ModelParams::V4),
+    SegmentModel::new(Cow::Borrowed("5"), ModelParams::V5),
+    SegmentModel::new(Cow::Borrowed("6"), ModelParams::V6),
+    SegmentModel::new(Cow::Borrowed("6-undirected"), ModelParams::V6_UNDIRECTED),
+    SegmentModel::new(Cow::Borrowed("7"), ModelParams::V7),
+    SegmentModel::new(Cow::Borrowed("8"), ModelParams::V8),
+    SegmentModel::new(Cow::Borrowed("9"), ModelParams::V9),
+    SegmentModel::new(Cow::Borrowed("10"), ModelParams::V10),
+    SegmentModel::new(Cow::Borrowed("6-combined"), ModelParams::V6_COMBINED),
+    SegmentModel::new(Cow::Borrowed("11"), ModelParams::V11),
+    SegmentModel::new(Cow::Borrowed("12"), ModelParams::V12),
+    SegmentModel::new(Cow::Borrowed("13"), ModelParams::V13),
+];
+
+impl FeatureExtractor for SegmentModel {
+    fn model_version(&self) -> &str {
+        &self.version
+    }
+
+    fn feature_colsize(&self) -> usize {
+        abs_feature_pyramid(&self.params.abs).colsize()
+            + rel_feature_pyramid(&self.params.rel).colsize()
+            + self
+                .params
+                .global
+                .as_ref()
+                .map_or(0, |global| global.colsize())
+            + self
+                .params
+                .stroke_types
+                .as_ref()
+                .map_or(0, |stroke_types| stroke_types.colsize())
+            + self
+                .params
+                .stroke_relations
+                .as_ref()
+                .map_or(0, |stroke_relations| stroke_relations.colsize())
+    }
+
+    fn extractor_params(&self) -> Option<ExtractorParams<'_>> {
+        Some(ExtractorParams::Segment(&self.params))
+    }
+
+    fn quantization(&self) -> Quantization {
+        self.params.quantization
+    }
+
+    fn strokes_to_feature_array(&self, strokes: &[Stroke]) -> Vec<f64> {
+        self.prepared_strokes_to_feature_array(&self.prepare_strokes(strokes))
+    }
+
+    fn pen_strokes_to_feature_array(&self, strokes: &[PenStroke]) -> Vec<f64> {
+        self.prepared_strokes_to_feature_array(&self.prepare_pen_strokes(strokes))
+    }
+
+    fn stroke_feature_array(&self, stroke: &Stroke) -> Option<Vec<f64>> {
+        self.is_additive()
+            .then(|| self.strokes_to_feature_array(std::slice::from_ref(stroke)))
+    }
+
+    fn explain_feature_array(&self, strokes: &[Stroke], top_cells: usize) -> Option<Explanation> {
+        let feature = self.strokes_to_feature_array(strokes);
+        let strokes = self.prepare_strokes(strokes);
+        let normalized_strokes = normalize_strokes(&strokes, &self.params.normalization);
+        let abs = abs_feature_pyramid(&self.params.abs);
+        let rel = rel_feature_pyramid(&self.params.rel);
+        let rel_offset = abs.colsize();
+
+        let mut elements = vec![];
+        for (stroke_index, stroke) in normalized_strokes.iter().enumerate() {
+            let segments = summary_segments(stroke, &self.params.summary);
+            let n_pieces = segments.len() - 1;
+            for (i, (p, q, k)) in segments.iter().enumerate() {
+                let role = match (i, n_pieces) {
+                    (0, _) => SegmentRole::StartEnd,
+                    (1, 2) => SegmentRole::StartMid,
+                    (2, 2) => SegmentRole::MidEnd,
+                    (i, _) => SegmentRole::Piece(i - 1),
+                };
+                // the elements of this segment alone
+                let mut raw_feature = RawFeature::new(&self.params);
+                raw_feature.add_feature_segment((p, q), *k);
+                let origin = (stroke_index, role);
+                for element in &raw_feature.abs {
+                    elements.push(self.explain_element(
+                        (FeatureBlock::Abs, &abs, 0),
+                        element,
+                        origin,
+                        top_cells,
+                    ));
+                }
+                for element in &raw_feature.rel {
+                    elements.push(self.explain_element(
+                        (FeatureBlock::Rel, &rel, rel_offset),
+                        element,
+                        origin,
+                        top_cells,
+                    ));
+                }
+            }
+        }
+        Some(Explanation { feature, elements })
+    }
+
+    fn cell_label(&self, cell: usize) -> Option<CellLabel> {
+        let abs_levels = abs_feature_pyramid(&self.params.abs)
+            .levels
+            .iter()
+            .map(|(grid, _)| (FeatureBlock::Abs, grid.dim().to_vec()))
+            .collect::<Vec<_>>();
+        let rel_levels = rel_feature_pyramid(&self.params.rel)
+            .levels
+            .iter()
+            .map(|(grid, _)| (FeatureBlock::Rel, grid.dim().to_vec()))
+            .collect::<Vec<_>>();
+        let mut offset = 0;
+        for levels in [abs_levels, rel_levels] {
+            for (level, (block, dim)) in levels.into_iter().enumerate() {
+                let size: usize = dim.iter().product();
+                if cell < offset + size {
+                    let coords = unravel_cell(cell - offset, &dim);
+                    return Some(CellLabel {
+                        block,
+                        level,
+                        coords,
+                    });
+                }
+                offset += size;
+            }
+        }
+
+        let params = &self.params;
+        let blocks = [
+            (
+                FeatureBlock::Global,
+                params.global.as_ref().map(|b| b.colsize()),
+            ),
+            (
+                FeatureBlock::StrokeTypes,
+                params.stroke_types.as_ref().map(|b| b.colsize()),
+            ),
+            (
+                FeatureBlock::StrokeRelations,
+                params.stroke_relations.as_ref().map(|b| b.colsize()),
+            ),
+        ];
+        for (block, size) in blocks {
+            let size = size.unwrap_or(0);
+            if cell < offset + size {
+                return Some(CellLabel {
+                    block,
+                    level: 0,
+                    coords: vec![cell - offset],
+                });
+            }
+            offset += size;
+        }
+        None
+    }
+}
+
+impl SegmentModel {
+    /// The strokes the rest of the pipeline works on, after the cleanup and the segmentation.
+    fn prepare_strokes<'a>(&self, strokes: &'a [Stroke]) -> Cow<'a, [Stroke]> {
+        let mut strokes = Cow::Borrowed(strokes);
+        if let Some(cleanup) = &self.params.cleanup {
+            strokes = Cow::Owned(cleanup_strokes(&strokes, cleanup));
+        }
+        if let Some(segmentation) = &self.params.segmentation {
+            strokes = Cow::Owned(segment_strokes(&strokes, segmentation));
+        }
+        strokes
+    }
+
+    /// Same as [`Self::prepare_strokes`] for pen strokes, whose pen data is used by the cleanup
+    /// and the segmentation and then dropped.
+    fn prepare_pen_strokes(&self, strokes: &[PenStroke]) -> Vec<Stroke> {
+        let mut strokes = Cow::Borrowed(strokes);
+        if let Some(cleanup) = &self.params.cleanup {
+            strokes = Cow::Owned(cleanup_pen_strokes(&strokes, cleanup));
+        }
+        if let Some(segmentation) = &self.params.segmentation {
+            strokes = Cow::Owned(segment_pen_strokes(&strokes, segmentation));
+        }
+        strokes.iter().map(Stroke::from).collect()
+    }
+
+    /// The feature of strokes that went through [`Self::prepare_strokes`].
+    fn prepared_strokes_to_feature_array(&self, strokes: &[Stroke]) -> Vec<f64> {
+        let normalized_strokes = normalize_strokes(strokes, &self.params.normalization);
+        let mut raw_feature = RawFeature::new(&self.params);
+        for stroke in normalized_strokes.iter() {
+            for (p, q, k) in summary_segments(stroke, &self.params.summary) {
+                raw_feature.add_feature_segment((&p, &q), k);
+            }
+        }
+        let mut feature_array = raw_feature.to_feature_array();
+        if let Some(global) = &self.params.global {
+            feature_array.extend(global.generate_feature_array(strokes));
+        }
+        if let Some(stroke_types) = &self.params.stroke_types {
+            feature_array.extend(stroke_types.generate_feature_array(&normalized_strokes));
+        }
+        if let Some(stroke_relations) = &self.params.stroke_relations {
+            feature_array.extend(stroke_relations.generate_feature_array(&normalized_strokes));
+        }
+        feature_array
+    }
 
-pub fn strokes_to_feature_array(strokes: &Vec<Stroke>) -> Vec<f64> {
-    let mut raw_feature = RawFeature::new();
-    for stroke in strokes {
-        let (start, mid, end) = stroke.summary_points();
+    /// Renders a single element onto the grids of its block, which start at `offset` in the
+    /// feature vector.
+    fn explain_element<const N: usize>(
+        &self,
+        (block, grid, offset): (FeatureBlock, &impl Feature<N>, usize),
+        element: &IndexedFeatureElement<N>,
+        (stroke, role): (usize, SegmentRole),
+        top_cells: usize,
+    ) -> ElementExplanation {
+        let mut cells: Vec<_> = grid
+            .generate_feature_array(std::slice::from_ref(element))
+            .into_iter()
+            .enumerate()
+            .map(|(i, contribution)| (offset + i, contribution))
+            .collect();
+        cells.sort_by(|(i, a), (j, b)| b.total_cmp(a).then(i.cmp(j)));
+        cells.truncate(top_cells);
+        ElementExplanation {
+            block,
+            stroke,
+            role,
+            index: element.index.to_vec(),
+            value: element.value,
+            top_cells: cells
+                .into_iter()
+                .map(|(cell, contribution)| CellContribution {
+                    cell,
+                    label: self.cell_label(cell),
+                    contribution,
+                })
+                .collect(),
+        }
+    }
+}
 
-        raw_feature.add_feature_segment((&start, &end), 1.0);
-        raw_feature.add_feature_segment((&start, &mid), 0.4);
-        raw_feature.add_feature_segment((&mid, &end), 0.4);
+/// Coordinates of the `i`-th cell of a grid laid out in row-major order.
+fn unravel_cell(mut i: usize, dim: &[usize]) -> Vec<usize> {
+    let mut coords = vec![0; dim.len()];
+    for (c, &d) in coords.iter_mut().zip(dim).rev() {
+        *c = i % d;
+        i /= d;
     }
-    raw_feature.to_feature_array()
+    coords
+}
+
+/// Reduces a stroke to weighted segments: the chord from the start to the end, followed by the
+/// segments between consecutive summary points.
+fn summary_segments(stroke: &Stroke, summary: &SummaryParams) -> Vec<(Point, Point, f64)> {
+    let points = match summary.mode {
+        SummaryMode::ThreePoints => {
+            let (start, mid, end) =
+                stroke.summary_points_by(summary.mid_point_threshold, summary.chord_distance);
+            vec![start, mid, end]
+        }
+        SummaryMode::Simplified { max_points } => {
+            let mut points = stroke.simplified_points(summary.mid_point_threshold, max_points);
+            if let [start, end] = &points[..] {
+                // split straight strokes at the middle as `summary_points` does
+                let mid = Point {
+                    x: (start.x + end.x) / 2.0,
+                    y: (start.y + end.y) / 2.0,
+                };
+                points.insert(1, mid);
+            }
+            points
+        }
+    };
+
+    let start = points.first().unwrap().clone();
+    let end = points.last().unwrap().clone();
+    let piece_weight = summary.half_weight * 2.0 / (points.len() - 1) as f64;
+    std::iter::once((start, end, summary.chord_weight))
+        .chain(
+            points
+                .windows(2)
+                .map(|w| (w[0].clone(), w[1].clone(), piece_weight)),
+        )
+        .collect()
 }
 
 #[derive(Debug, Clone)]
-struct RawFeature {
+struct RawFeature<'a> {
+    params: &'a ModelParams,
     abs: Vec<AbsFeatureElement>,
     rel: Vec<RelFeatureElement>,
 }
 
-impl RawFeature {
-    fn new() -> Self {
+impl<'a> RawFeature<'a> {
+    fn new(params: &'a ModelParams) -> Self {
         let abs = Vec::new();
         let rel = Vec::new();
-        RawFeature { abs, rel }
+        RawFeature { params, abs, rel }
     }
 
     fn add_feature_segment(&mut self, segment: (&Point, &Point), k: f64) {
-        self.abs.push(calc_abs_index(segment, k));
-        self.rel.push(calc_rel_index(segment, k));
+        let (p, q) = segment;
+        let rel = &self.params.rel;
+        match self.params.direction {
+            SegmentDirection::Directed => {
+                self.abs.push(calc_abs_index(segment, k));
+                self.rel.push(calc_rel_index(segment, k, rel, false));
+            }
+            SegmentDirection::Undirected => {
+                self.abs.push(calc_abs_index((p, q), k / 2.0));
+                self.abs.push(calc_abs_index((q, p), k / 2.0));
+                self.rel.push(calc_rel_index(segment, k, rel, true));
+            }
+            SegmentDirection::Symmetric => {
+                self.abs.push(calc_abs_index((p, q), k / 2.0));
+                self.abs.push(calc_abs_index((q, p), k / 2.0));
+                self.rel.push(calc_rel_index((p, q), k / 2.0, rel, false));
+                self.rel.push(calc_rel_index((q, p), k / 2.0, rel, false));
+            }
+        }
     }
 
     fn to_feature_array(&self) -> Vec<f64> {
-        AbsFeatureDim::generate_feature_array(&self.abs)
+        abs_feature_pyramid(&self.params.abs)
+            .generate_feature_array(&self.abs)
             .into_iter()
-            .chain(RelFeatureDim::generate_feature_array(&self.rel))
+            .chain(rel_feature_pyramid(&self.params.rel).generate_feature_array(&self.rel))
             .collect()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extractor::{FeatureExtractor, get_extractor};
+
+    fn stroke(points: &[(f64, f64)]) -> Stroke {
+        Stroke(points.iter().map(|&(x, y)| Point { x, y }).collect())
+    }
+
+    /// Indexes built with v2 must stay searchable, so its features must not change.
+    #[test]
+    fn v2_matches_baseline() {
+        let strokes = [
+            stroke(&[
+                (100.0, 20.0),
+                (100.0, 100.0),
+                (97.0, 129.0),
+                (89.0, 151.0),
+                (75.0, 167.0),
+                (55.0, 177.0),
+                (30.0, 180.0),
+            ]),
+            stroke(&[
+                (30.0, 20.0),
+                (58.0, 37.0),
+                (86.0, 49.0),
+                (114.0, 55.0),
+                (142.0, 55.0),
+                (170.0, 50.0),
+            ]),
+            stroke(&[(40.0, 40.0), (160.0, 40.0), (160.0, 160.0)]),
+        ];
+        let baseline: Vec<f64> = include_str!("../testdata/v2_baseline.txt")
+            .lines()
+            .map(|line| line.parse().unwrap())
+            .collect();
+        let feature = get_extractor("2")
+            .unwrap()
+            .strokes_to_feature_array(&strokes);
+        assert_eq!(feature.len(), baseline.len());
+        for (i, (value, expected)) in feature.iter().zip(&baseline).enumerate() {
+            assert!(
+                (value - expected).abs() <= 1e-12,
+                "element {}: {} != {}",
+                i,
+                value,
+                expected
+            );
+        }
+    }
+
+    fn reversed(strokes: &[Stroke]) -> Vec<Stroke> {
+        strokes
+            .iter()
+            .map(|stroke| Stroke(stroke.0.iter().rev().cloned().collect()))
+            .collect()
+    }
+
+    fn max_difference(a: &[f64], b: &[f64]) -> f64 {
+        a.iter()
+            .zip(b)
+            .map(|(x, y)| (x - y).abs())
+            .fold(0.0, f64::max)
+    }
+
+    #[test]
+    fn undirected_features_ignore_the_drawing_direction() {
+        let strokes = [
+            stroke(&[(30.0, 20.0), (100.0, 60.0), (170.0, 50.0)]),
+            stroke(&[(100.0, 20.0), (100.0, 100.0), (60.0, 170.0)]),
+        ];
+        let symmetric = SegmentModel::new(
+            Cow::Borrowed("6-symmetric"),
+            ModelParams {
+                direction: SegmentDirection::Symmetric,
+                ..ModelParams::V6
+            },
+        );
+        let undirected = get_extractor("6-undirected").unwrap();
+        for model in [undirected, &symmetric as &dyn FeatureExtractor] {
+            let a = model.strokes_to_feature_array(&strokes);
+            let b = model.strokes_to_feature_array(&reversed(&strokes));
+            assert!(max_difference(&a, &b) < 1e-12);
+        }
+        let directed = get_extractor("6").unwrap();
+        let a = directed.strokes_to_feature_array(&strokes);
+        let b = directed.strokes_to_feature_array(&reversed(&strokes));
+        assert!(max_difference(&a, &b) > 0.1);
+    }
+}
@@ -0,0 +1,273 @@
+use std::borrow::Cow;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    canvas::CanvasSpec,
+    geometry::{BoundingBox, distance},
+    stroke::{Point, Stroke},
+};
+
+/// Size of the square frame (GlyphWiki's coordinate system) the strokes are normalized into.
+const FRAME_SIZE: f64 = CanvasSpec::GLYPHWIKI.width;
+
+/// Geometric normalization applied to the strokes before feature extraction.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "method", rename_all = "snake_case"))]
+pub enum Normalization {
+    /// The strokes are used as they are.
+    #[default]
+    None,
+    /// The bounding box of the strokes is fitted into the frame, leaving `margin` on each side.
+    BoundingBox {
+        preserve_aspect_ratio: bool,
+        margin: f64,
+    },
+    /// The centroid of the ink is moved to the centre of the frame and the strokes are scaled
+    /// so that the standard deviation of the ink around it becomes `spread`.
+    Moment {
+        preserve_aspect_ratio: bool,
+        spread: f64,
+    },
+}
+
+/// Applies `normalization` to `strokes`. Both the query and the indexed glyphs must go through
+/// the same normalization for their features to be comparable.
+pub fn normalize_strokes<'a>(
+    strokes: &'a [Stroke],
+    normalization: &Normalization,
+) -> Cow<'a, [Stroke]> {
+    let transform = match *normalization {
+        Normalization::None => None,
+        Normalization::BoundingBox {
+            preserve_aspect_ratio,
+            margin,
+        } => bounding_box_transform(strokes, preserve_aspect_ratio, margin),
+        Normalization::Moment {
+            preserve_aspect_ratio,
+            spread,
+        } => moment_transform(strokes, preserve_aspect_ratio, spread),
+    };
+    match transform {
+        Some(transform) => Cow::Owned(transform.apply(strokes)),
+        None => Cow::Borrowed(strokes),
+    }
+}
+
+/// Maps `p` to `(p - origin) * scale + (FRAME_SIZE / 2)` on each axis.
+#[derive(Debug, Clone, Copy)]
+struct Transform {
+    origin: (f64, f64),
+    scale: (f64, f64),
+}
+
+impl Transform {
+    fn new(
+        origin: (f64, f64),
+        extent: (f64, f64),
+        target: f64,
+        preserve_aspect_ratio: bool,
+    ) -> Self {
+        let scale_of = |extent: f64| (extent > 0.0).then(|| target / extent);
+        let scale = if preserve_aspect_ratio {
+            let scale = scale_of(extent.0.max(extent.1)).unwrap_or(1.0);
+            (scale, scale)
+        } else {
+            // an axis without extent (e.g. a single vertical line) follows the other axis
+            match (scale_of(extent.0), scale_of(extent.1)) {
+                (Some(sx), Some(sy)) => (sx, sy),
+                (Some(s), None) | (None, Some(s)) => (s, s),
+                (None, None) => (1.0, 1.0),
+            }
+        };
+        Transform { origin, scale }
+    }
+
+    fn apply(&self, strokes: &[Stroke]) -> Vec<Stroke> {
+        let center = FRAME_SIZE / 2.0;
+        strokes
+            .iter()
+            .map(|stroke| {
+                Stroke(
+                    stroke
+                        .0
+                        .iter()
+                        .map(|p| Point {
+                            x: (p.x - self.origin.0) * self.scale.0 + center,
+                            y: (p.y - self.origin.1) * self.scale.1 + center,
+                        })
+                        .collect(),
+                )
+            })
+            .collect()
+    }
+}
+
+fn bounding_box_transform(
+    strokes: &[Stroke],
+    preserve_aspect_ratio: bool,
+    margin: f64,
+) -> Option<Transform> {
+    let bbox = BoundingBox::of(strokes.iter().flat_map(|stroke| &stroke.0))?;
+    Some(Transform::new(
+        bbox.center(),
+        (bbox.width(), bbox.height()),
+        FRAME_SIZE - 2.0 * margin,
+        preserve_aspect_ratio,
+    ))
+}
+
+fn moment_transform(
+    strokes: &[Stroke],
+    preserve_aspect_ratio: bool,
+    spread: f64,
+) -> Option<Transform> {
+    // The moments are integrated along the polylines so that they do not depend on how densely
+    // the strokes are sampled. Only when there is no ink length at all (e.g. a single dot) are
+    // the points themselves averaged.
+    let mut weight = 0.0;
+    let (mut sum_x, mut sum_y, mut sum_xx, mut sum_yy) = (0.0, 0.0, 0.0, 0.0);
+    for stroke in strokes {
+        for (p, q) in stroke.0.iter().zip(stroke.0.iter().skip(1)) {
+            let len = distance(p, q);
+            weight += len;
+            sum_x += len * (p.x + q.x) / 2.0;
+            sum_y += len * (p.y + q.y) / 2.0;
+            sum_xx += len * (p.x * p.x + p.x * q.x + q.x * q.x) / 3.0;
+            sum_yy += len * (p.y * p.y + p.y * q.y + q.y * q.y) / 3.0;
+        }
+    }
+    if weight == 0.0 {
+        for p in strokes.iter().flat_map(|stroke| &stroke.0) {
+            weight += 1.0;
+            sum_x += p.x;
+            sum_y += p.y;
+            sum_xx += p.x * p.x;
+            sum_yy += p.y * p.y;
+        }
+    }
+    if weight == 0.0 {
+        return None;
+    }
+
+    let mean = (sum_x / weight, sum_y / weight);
+    let std_dev = |sum_sq: f64, mean: f64| (sum_sq / weight - mean * mean).max(0.0).sqrt();
+    Some(Transform::new(
+        mean,
+        (std_dev(sum_xx, mean.0), std_dev(sum_yy, mean.1)),
+        spread,
+        preserve_aspect_ratio,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extractor::get_extractor;
+
+    fn glyph() -> Vec<Stroke> {
+        [
+            vec![(40.0, 50.0), (160.0, 50.0)],
+            vec![(100.0, 20.0), (100.0, 180.0)],
+            vec![(60.0, 120.0), (80.0, 150.0), (140.0, 160.0)],
+        ]
+        .into_iter()
+        .map(|points| Stroke(points.into_iter().map(Point::from).collect()))
+        .collect()
+    }
+
+    /// The glyph drawn small in the top-left corner, as on a touch screen.
+    fn moved_glyph() -> Vec<Stroke> {
+        glyph()
+            .iter()
+            .map(|stroke| {
+                Stroke(
+                    stroke
+                        .0
+                        .iter()
+                        .map(|p| Point {
+                            x: p.x * 0.4 + 7.0,
+                            y: p.y * 0.4 - 3.0,
+                        })
+                        .collect(),
+                )
+            })
+            .collect()
+    }
+
+    fn assert_close(a: &[f64], b: &[f64]) {
+        assert_eq!(a.len(), b.len());
+        for (i, (x, y)) in a.iter().zip(b).enumerate() {
+            assert!((x - y).abs() < 1e-9, "element {}: {} != {}", i, x, y);
+        }
+    }
+
+    fn coordinates(strokes: &[Stroke]) -> Vec<f64> {
+        strokes
+            .iter()
+            .flat_map(|stroke| stroke.0.iter().flat_map(|p| [p.x, p.y]))
+            .collect()
+    }
+
+    #[test]
+    fn translation_and_scale_are_normalized_away() {
+        for normalization in [
+            Normalization::BoundingBox {
+                preserve_aspect_ratio: true,
+                margin: 10.0,
+            },
+            Normalization::Moment {
+                preserve_aspect_ratio: false,
+                spread: 45.0,
+            },
+        ] {
+            assert_close(
+                &coordinates(&normalize_strokes(&glyph(), &normalization)),
+                &coordinates(&normalize_strokes(&moved_glyph(), &normalization)),
+            );
+        }
+    }
+
+    #[test]
+    fn normalized_model_gives_the_same_feature() {
+        // v3 is v4 without the normalization
+        let v3 = get_extractor("3").unwrap();
+        let v4 = get_extractor("4").unwrap();
+        assert_close(
+            &v4.strokes_to_feature_array(&glyph()),
+            &v4.strokes_to_feature_array(&moved_glyph()),
+        );
+        let (a, b) = (
+            v3.strokes_to_feature_array(&glyph()),
+            v3.strokes_to_feature_array(&moved_glyph()),
+        );
+        assert!(a.iter().zip(&b).any(|(x, y)| (x - y).abs() > 0.1));
+    }
+
+    #[test]
+    fn bounding_box_fits_the_frame() {
+        let normalization = Normalization::BoundingBox {
+            preserve_aspect_ratio: false,
+            margin: 10.0,
+        };
+        let strokes = moved_glyph();
+        let normalized = normalize_strokes(&strokes, &normalization);
+        let bbox = BoundingBox::of(normalized.iter().flat_map(|stroke| &stroke.0)).unwrap();
+        assert_close(
+            &[bbox.min_x, bbox.max_x, bbox.min_y, bbox.max_y],
+            &[10.0, 190.0, 10.0, 190.0],
+        );
+        // a dot cannot be scaled, only moved to the centre
+        let dot = [Stroke(vec![Point { x: 3.0, y: 4.0 }])];
+        assert_close(
+            &coordinates(&normalize_strokes(&dot, &normalization)),
+            &[100.0, 100.0],
+        );
+        assert!(matches!(
+            normalize_strokes(&dot, &Normalization::None),
+            Cow::Borrowed(_)
+        ));
+    }
+}
@@ -0,0 +1,459 @@
+use std::borrow::Cow;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    cleanup::CleanupParams, error::ModelError, global_feature::GlobalFeatureParams,
+    normalize::Normalization, quantize::Quantization, segmentation::SegmentationParams,
+    stroke::ChordDistance, stroke_relation::StrokeRelationParams, stroke_type::StrokeTypeParams,
+};
+
+/// Tuning knobs of the segment model.
+///
+/// Lengths are in units of the 200x200 frame the strokes are mapped into (see
+/// [`crate::CanvasSpec`]), so they are relative to the size of the input canvas.
+///
+/// The presets reproduce the registered model versions; other values can be loaded at runtime
+/// (e.g. from a JSON file with the `serde` feature) to experiment without recompiling.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ModelParams {
+    /// Cleanup of raw input strokes, applied before anything else.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub cleanup: Option<CleanupParams>,
+    /// Re-segmentation of the strokes after the cleanup (joining broken strokes and splitting
+    /// connected ones).
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub segmentation: Option<SegmentationParams>,
+    /// Geometric normalization of the strokes before they are summarized.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub normalization: Normalization,
+    /// Grid for the absolute positions of segment endpoints.
+    pub abs: AbsGridParams,
+    /// Grid for the position, length and direction of segments.
+    pub rel: RelGridParams,
+    /// How each stroke is turned into weighted segments.
+    pub summary: SummaryParams,
+    /// Whether the drawing direction of segments is taken into account.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub direction: SegmentDirection,
+    /// Block of global shape descriptors appended after the Gaussian blocks, if any.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub global: Option<GlobalFeatureParams>,
+    /// Positional histogram of stroke types appended after the global descriptors, if any.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub stroke_types: Option<StrokeTypeParams>,
+    /// Positional block of relations between strokes appended after the stroke types, if any.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub stroke_relations: Option<StrokeRelationParams>,
+    /// Mapping used when the features are quantized.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub quantization: Quantization,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AbsGridParams {
+    pub n_x: usize,
+    pub n_y: usize,
+    /// Grids of other resolutions the same segments are also rendered onto.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub extra_levels: Cow<'static, [AbsGridLevel]>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AbsGridLevel {
+    pub n_x: usize,
+    pub n_y: usize,
+    /// Multiplier of this level relative to the main grid.
+    pub weight: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RelGridParams {
+    pub n_x: usize,
+    pub n_y: usize,
+    pub n_mag: usize,
+    pub n_angle: usize,
+    pub angle_axis: AngleAxis,
+    /// Overall multiplier of the relative block.
+    pub gain: f64,
+    /// Segment length at which the weight of a segment grows by 1/2 of the base weight.
+    pub mag_weight_scale: f64,
+    /// Grids of other resolutions the same segments are also rendered onto.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub extra_levels: Cow<'static, [RelGridLevel]>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RelGridLevel {
+    pub n_x: usize,
+    pub n_y: usize,
+    pub n_mag: usize,
+    pub n_angle: usize,
+    /// Multiplier of this level relative to the main grid.
+    pub weight: f64,
+}
+
+/// How a segment direction is laid out on the angle axis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum AngleAxis {
+    /// Angles in `[-pi/2, pi]` are spread over the axis and the rest are clamped to its ends.
+    Clamped,
+    /// The whole circle is spread over a periodic axis.
+    Periodic,
+}
+
+/// How the drawing direction of a segment affects its features. Handwritten strokes are often
+/// drawn in the opposite direction from the reference glyphs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum SegmentDirection {
+    /// Segments drawn in opposite directions have different features.
+    #[default]
+    Directed,
+    /// The angle is folded to an orientation in `[0, pi)` spread over the whole angle axis, and
+    /// the absolute block takes the segment in both directions at half weight.
+    Undirected,
+    /// The segment is taken in both directions at half weight in both blocks.
+    Symmetric,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SummaryParams {
+    /// Weight of the segment from the start to the end of a stroke.
+    pub chord_weight: f64,
+    /// Weight of each of the segments start-mid and mid-end.
+    pub half_weight: f64,
+    /// Minimum distance from the chord for a point to be taken as the mid point of a stroke.
+    pub mid_point_threshold: f64,
+    /// How the distance from the chord is measured.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub chord_distance: ChordDistance,
+    /// Which points of a stroke are used.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub mode: SummaryMode,
+}
+
+/// How a stroke is reduced to the points its segments are taken between.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "kind", rename_all = "snake_case"))]
+pub enum SummaryMode {
+    /// The start, mid and end points (see [`crate::Stroke::summary_points_by`]).
+    #[default]
+    ThreePoints,
+    /// Up to `max_points` corner points found by polyline simplification (see
+    /// [`crate::Stroke::simplified_points`]), with `mid_point_threshold` as the tolerance.
+    /// The `2 * half_weight` is split evenly among the segments between consecutive points, so
+    /// the total weight of a stroke is the same as with [`SummaryMode::ThreePoints`].
+    Simplified { max_points: usize },
+}
+
+impl ModelParams {
+    pub const V2: ModelParams = ModelParams {
+        cleanup: None,
+        segmentation: None,
+        normalization: Normalization::None,
+        abs: AbsGridParams {
+            n_x: 2,
+            n_y: 2,
+            extra_levels: Cow::Borrowed(&[]),
+        },
+        rel: RelGridParams {
+            n_x: 3,
+            n_y: 3,
+            n_mag: 6,
+            n_angle: 7,
+            angle_axis: AngleAxis::Clamped,
+            gain: 1.3,
+            mag_weight_scale: 400.0,
+            extra_levels: Cow::Borrowed(&[]),
+        },
+        summary: SummaryParams {
+            chord_weight: 1.0,
+            half_weight: 0.4,
+            mid_point_threshold: 7.0,
+            chord_distance: ChordDistance::Legacy,
+            mode: SummaryMode::ThreePoints,
+        },
+        direction: SegmentDirection::Directed,
+        global: None,
+        stroke_types: None,
+        stroke_relations: None,
+        quantization: Quantization::SEGMENT_MODEL,
+    };
+
+    pub const V3: ModelParams = {
+        let mut params = Self::V2;
+        params.rel.n_angle = 8;
+        params.rel.angle_axis = AngleAxis::Periodic;
+        params
+    };
+
+    pub const V4: ModelParams = {
+        let mut params = Self::V3;
+        params.normalization = Normalization::Moment {
+            preserve_aspect_ratio: true,
+            spread: 45.0,
+        };
+        params
+    };
+
+    pub const V5: ModelParams = {
+        let mut params = Self::V4;
+        params.summary.chord_distance = ChordDistance::Perpendicular;
+        params
+    };
+
+    pub const V6: ModelParams = {
+        let mut params = Self::V5;
+        params.summary.mode = SummaryMode::Simplified { max_points: 5 };
+        params
+    };
+
+    /// V6 with undirected segments. The angle axis covers half the circle, so it has half the
+    /// cells to keep the same angular resolution.
+    pub const V6_UNDIRECTED: ModelParams = {
+        let mut params = Self::V6;
+        params.rel.n_angle = 4;
+        params.direction = SegmentDirection::Undirected;
+        params
+    };
+
+    pub const V7: ModelParams = {
+        let mut params = Self::V6;
+        params.global = Some(GlobalFeatureParams {
+            weight: 3.0,
+            n_stroke_count: 8,
+            max_stroke_count: 30.0,
+            n_length: 6,
+            max_length: 30.0,
+            n_aspect_ratio: 5,
+            max_aspect_ratio: 4.0,
+            n_centroid: 3,
+        });
+        params
+    };
+
+    /// V6 with a finer absolute grid, and a position-independent relative grid that is
+    /// tolerant to sloppy placement.
+    pub const V8: ModelParams = ModelParams {
+        abs: AbsGridParams {
+            extra_levels: Cow::Borrowed(&[AbsGridLevel {
+                n_x: 3,
+                n_y: 3,
+                weight: 0.5,
+            }]),
+            ..Self::V6.abs
+        },
+        rel: RelGridParams {
+            extra_levels: Cow::Borrowed(&[RelGridLevel {
+                n_x: 1,
+                n_y: 1,
+                n_mag: 6,
+                n_angle: 8,
+                weight: 1.0,
+            }]),
+            ..Self::V6.rel
+        },
+        ..Self::V6
+    };
+
+    /// V6 with a 3x3 histogram of stroke types.
+    pub const V9: ModelParams = ModelParams {
+        stroke_types: Some(StrokeTypeParams {
+            weight: 2.0,
+            n_x: 3,
+            n_y: 3,
+        }),
+        ..Self::V6
+    };
+
+    /// V6 with the crossings, junctions, near-parallel pairs and enclosures of the strokes.
+    pub const V10: ModelParams = ModelParams {
+        stroke_relations: Some(StrokeRelationParams {
+            weight: 2.0,
+            n_x: 3,
+            n_y: 3,
+            touch_distance: 8.0,
+            parallel_angle: std::f64::consts::PI / 12.0,
+            parallel_distance: 60.0,
+        }),
+        ..Self::V6
+    };
+
+    /// V6 with the blocks of V7 to V10 together.
+    pub const V6_COMBINED: ModelParams = ModelParams {
+        abs: Self::V8.abs,
+        rel: Self::V8.rel,
+        global: Self::V7.global,
+        stroke_types: Self::V9.stroke_types,
+        stroke_relations: Self::V10.stroke_relations,
+        ..Self::V6
+    };
+
+    /// V6_COMBINED on cleaned-up strokes.
+    pub const V11: ModelParams = ModelParams {
+        cleanup: Some(CleanupParams::DEFAULT),
+        ..Self::V6_COMBINED
+    };
+
+    /// V11 with the strokes re-segmented, for semi-cursive writing and broken strokes.
+    pub const V12: ModelParams = ModelParams {
+        segmentation: Some(SegmentationParams::DEFAULT),
+        ..Self::V11
+    };
+
+    /// V12 with the preprocessing using the pressure and time of pen strokes, where given.
+    pub const V13: ModelParams = ModelParams {
+        cleanup: Some(CleanupParams::PEN_AWARE),
+        segmentation: Some(SegmentationParams::PEN_AWARE),
+        ..Self::V12
+    };
+
+    /// Checks that every grid has at least one cell on each axis and that the weights, lengths
+    /// and scales are finite and non-negative (and positive where they divide).
+    ///
+    /// The presets always pass; parameters loaded at runtime should be checked before they are
+    /// used, as the feature extraction assumes them to be valid.
+    pub fn check(&self) -> Result<(), ModelError> {
+        if let Some(cleanup) = &self.cleanup {
+            non_negative("cleanup.min_point_distance", cleanup.min_point_distance)?;
+            non_negative("cleanup.max_hook_length", cleanup.max_hook_length)?;
+            non_negative("cleanup.min_hook_angle", cleanup.min_hook_angle)?;
+            non_negative("cleanup.max_hook_duration", cleanup.max_hook_duration)?;
+            non_negative("cleanup.max_hook_pressure", cleanup.max_hook_pressure)?;
+            non_negative("cleanup.resample_step", cleanup.resample_step)?;
+            non_negative("cleanup.smoothing_speed", cleanup.smoothing_speed)?;
+        }
+        if let Some(segmentation) = &self.segmentation {
+            non_negative(
+                "segmentation.corner_tolerance",
+                segmentation.corner_tolerance,
+            )?;
+            non_negative("segmentation.split_angle", segmentation.split_angle)?;
+            non_negative(
+                "segmentation.min_piece_length",
+                segmentation.min_piece_length,
+            )?;
+            non_negative("segmentation.merge_distance", segmentation.merge_distance)?;
+            non_negative("segmentation.merge_angle", segmentation.merge_angle)?;
+            non_negative("segmentation.max_merge_pause", segmentation.max_merge_pause)?;
+        }
+        match self.normalization {
+            Normalization::None => {}
+            Normalization::BoundingBox { margin, .. } => {
+                non_negative("normalization.margin", margin)?
+            }
+            Normalization::Moment { spread, .. } => non_negative("normalization.spread", spread)?,
+        }
+
+        dim("abs.n_x", self.abs.n_x)?;
+        dim("abs.n_y", self.abs.n_y)?;
+        for (i, level) in self.abs.extra_levels.iter().enumerate() {
+            dim(&format!("abs.extra_levels[{}].n_x", i), level.n_x)?;
+            dim(&format!("abs.extra_levels[{}].n_y", i), level.n_y)?;
+            non_negative(&format!("abs.extra_levels[{}].weight", i), level.weight)?;
+        }
+
+        dim("rel.n_x", self.rel.n_x)?;
+        dim("rel.n_y", self.rel.n_y)?;
+        dim("rel.n_mag", self.rel.n_mag)?;
+        dim("rel.n_angle", self.rel.n_angle)?;
+        non_negative("rel.gain", self.rel.gain)?;
+        positive("rel.mag_weight_scale", self.rel.mag_weight_scale)?;
+        for (i, level) in self.rel.extra_levels.iter().enumerate() {
+            dim(&format!("rel.extra_levels[{}].n_x", i), level.n_x)?;
+            dim(&format!("rel.extra_levels[{}].n_y", i), level.n_y)?;
+            dim(&format!("rel.extra_levels[{}].n_mag", i), level.n_mag)?;
+            dim(&format!("rel.extra_levels[{}].n_angle", i), level.n_angle)?;
+            non_negative(&format!("rel.extra_levels[{}].weight", i), level.weight)?;
+        }
+
+        non_negative("summary.chord_weight", self.summary.chord_weight)?;
+        non_negative("summary.half_weight", self.summary.half_weight)?;
+        non_negative(
+            "summary.mid_point_threshold",
+            self.summary.mid_point_threshold,
+        )?;
+        if let SummaryMode::Simplified { max_points } = self.summary.mode {
+            // the start and end points are always kept
+            if max_points < 2 {
+                return Err(invalid("summary.mode.max_points"));
+            }
+        }
+
+        if let Some(global) = &self.global {
+            non_negative("global.weight", global.weight)?;
+            dim("global.n_stroke_count", global.n_stroke_count)?;
+            dim("global.n_length", global.n_length)?;
+            dim("global.n_aspect_ratio", global.n_aspect_ratio)?;
+            dim("global.n_centroid", global.n_centroid)?;
+            // these are divided by after subtracting 1 or taking the logarithm
+            greater_than("global.max_stroke_count", global.max_stroke_count, 1.0)?;
+            positive("global.max_length", global.max_length)?;
+            greater_than("global.max_aspect_ratio", global.max_aspect_ratio, 1.0)?;
+        }
+        if let Some(stroke_types) = &self.stroke_types {
+            non_negative("stroke_types.weight", stroke_types.weight)?;
+            dim("stroke_types.n_x", stroke_types.n_x)?;
+            dim("stroke_types.n_y", stroke_types.n_y)?;
+        }
+        if let Some(relations) = &self.stroke_relations {
+            non_negative("stroke_relations.weight", relations.weight)?;
+            dim("stroke_relations.n_x", relations.n_x)?;
+            dim("stroke_relations.n_y", relations.n_y)?;
+            non_negative("stroke_relations.touch_distance", relations.touch_distance)?;
+            non_negative("stroke_relations.parallel_angle", relations.parallel_angle)?;
+            non_negative(
+                "stroke_relations.parallel_distance",
+                relations.parallel_distance,
+            )?;
+        }
+
+        positive("quantization.scale", self.quantization.scale)?;
+        if !self.quantization.offset.is_finite() {
+            return Err(invalid("quantization.offset"));
+        }
+        Ok(())
+    }
+}
+
+fn invalid(field: &str) -> ModelError {
+    ModelError::InvalidParams {
+        field: field.to_string(),
+    }
+}
+
+fn dim(field: &str, n: usize) -> Result<(), ModelError> {
+    if n == 0 { Err(invalid(field)) } else { Ok(()) }
+}
+
+fn non_negative(field: &str, x: f64) -> Result<(), ModelError> {
+    if x.is_finite() && x >= 0.0 {
+        Ok(())
+    } else {
+        Err(invalid(field))
+    }
+}
+
+fn positive(field: &str, x: f64) -> Result<(), ModelError> {
+    greater_than(field, x, 0.0)
+}
+
+fn greater_than(field: &str, x: f64, min: f64) -> Result<(), ModelError> {
+    if x.is_finite() && x > min {
+        Ok(())
+    } else {
+        Err(invalid(field))
+    }
+}
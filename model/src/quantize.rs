@@ -0,0 +1,248 @@
+use half::f16;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+/// Linear mapping between feature values and quantized codes: `value = code * scale + offset`.
+///
+/// Each model has a default mapping (see [`crate::FeatureExtractor::quantization`]), but an index
+/// is quantized with a mapping fitted to the range of its features (see [`Quantization::fit`]).
+/// The mapping must be recorded alongside a quantized index, so that queries can be quantized
+/// the same way and results dequantized.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Quantization {
+    pub scale: f64,
+    pub offset: f64,
+}
+
+/// Storage type of quantized features.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum QuantizedFormat {
+    /// Codes in `0..=255`; values out of range are saturated.
+    U8,
+    /// Half-precision floats, stored as their bit patterns. Values beyond the largest finite
+    /// half-precision float are saturated to it.
+    F16,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum QuantizedFeature {
+    U8(Vec<u8>),
+    F16(Vec<u16>),
+}
+
+impl Quantization {
+    /// Default of the segment models, whose features are non-negative: values up to 20.4 in
+    /// steps of 0.08. Larger values saturate in u8, so indexes use a mapping fitted to their
+    /// features instead.
+    pub const SEGMENT_MODEL: Quantization = Quantization {
+        scale: 0.08,
+        offset: 0.0,
+    };
+
+    /// The mapping that spreads `min..=max` over the whole range of u8 codes, so that no value
+    /// in the range saturates. The same mapping is used for f16, which is then as fine as u8
+    /// at the top of the range and finer below.
+    pub fn fit(min: f64, max: f64) -> Quantization {
+        let scale = (max - min) / u8::MAX as f64;
+        Quantization {
+            // a constant feature still needs a valid mapping
+            scale: if scale > 0.0 { scale } else { 1.0 },
+            offset: min,
+        }
+    }
+
+    /// The number of values in `feature` that are out of the range of `format` and are
+    /// saturated by [`Self::quantize`].
+    pub fn saturated_count(&self, feature: &[f64], format: QuantizedFormat) -> usize {
+        let max_code = match format {
+            QuantizedFormat::U8 => u8::MAX as f64,
+            QuantizedFormat::F16 => f16::MAX.to_f64(),
+        };
+        let min_code = match format {
+            QuantizedFormat::U8 => 0.0,
+            QuantizedFormat::F16 => f16::MIN.to_f64(),
+        };
+        feature
+            .iter()
+            .map(|x| ((x - self.offset) / self.scale).round())
+            .filter(|&code| !(min_code..=max_code).contains(&code))
+            .count()
+    }
+
+    pub fn quantize(&self, feature: &[f64], format: QuantizedFormat) -> QuantizedFeature {
+        match format {
+            QuantizedFormat::U8 => QuantizedFeature::U8(self.quantize_u8(feature)),
+            QuantizedFormat::F16 => QuantizedFeature::F16(self.quantize_f16(feature)),
+        }
+    }
+
+    pub fn dequantize(&self, feature: &QuantizedFeature) -> Vec<f64> {
+        match feature {
+            QuantizedFeature::U8(codes) => self.dequantize_u8(codes),
+            QuantizedFeature::F16(codes) => self.dequantize_f16(codes),
+        }
+    }
+
+    pub fn quantize_u8(&self, feature: &[f64]) -> Vec<u8> {
+        feature
+            .iter()
+            .map(|x| {
+                let code = ((x - self.offset) / self.scale).round();
+                code.clamp(0.0, u8::MAX as f64) as u8
+            })
+            .collect()
+    }
+
+    pub fn dequantize_u8(&self, codes: &[u8]) -> Vec<f64> {
+        codes
+            .iter()
+            .map(|&code| code as f64 * self.scale + self.offset)
+            .collect()
+    }
+
+    pub fn quantize_f16(&self, feature: &[f64]) -> Vec<u16> {
+        feature
+            .iter()
+            .map(|x| {
+                let code = (x - self.offset) / self.scale;
+                f16::from_f64(code.clamp(f16::MIN.to_f64(), f16::MAX.to_f64())).to_bits()
+            })
+            .collect()
+    }
+
+    pub fn dequantize_f16(&self, codes: &[u16]) -> Vec<f64> {
+        codes
+            .iter()
+            .map(|&code| f16::from_bits(code).to_f64() * self.scale + self.offset)
+            .collect()
+    }
+}
+
+#[wasm_bindgen]
+impl Quantization {
+    #[wasm_bindgen(constructor)]
+    pub fn new(scale: f64, offset: f64) -> Self {
+        Quantization { scale, offset }
+    }
+
+    #[wasm_bindgen(js_name = quantize_u8)]
+    pub fn quantize_u8_array(&self, feature: &[f64]) -> Box<[u8]> {
+        self.quantize_u8(feature).into()
+    }
+
+    #[wasm_bindgen(js_name = dequantize_u8)]
+    pub fn dequantize_u8_array(&self, codes: &[u8]) -> Box<[f64]> {
+        self.dequantize_u8(codes).into()
+    }
+
+    #[wasm_bindgen(js_name = quantize_f16)]
+    pub fn quantize_f16_array(&self, feature: &[f64]) -> Box<[u16]> {
+        self.quantize_f16(feature).into()
+    }
+
+    #[wasm_bindgen(js_name = dequantize_f16)]
+    pub fn dequantize_f16_array(&self, codes: &[u16]) -> Box<[f64]> {
+        self.dequantize_f16(codes).into()
+    }
+}
+
+impl Default for Quantization {
+    fn default() -> Self {
+        Self::SEGMENT_MODEL
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FEATURE: [f64; 6] = [0.0, 0.013, 1.5, 7.25, 19.9, 31.0];
+
+    #[test]
+    fn u8_round_trip() {
+        let quantization = Quantization::fit(0.0, 31.0);
+        assert_eq!(
+            quantization.saturated_count(&FEATURE, QuantizedFormat::U8),
+            0
+        );
+        let codes = quantization.quantize_u8(&FEATURE);
+        assert_eq!(codes[0], 0);
+        assert_eq!(codes[5], u8::MAX);
+        let QuantizedFeature::U8(quantized) = quantization.quantize(&FEATURE, QuantizedFormat::U8)
+        else {
+            panic!("not u8 codes");
+        };
+        assert_eq!(quantized, codes);
+        for (x, y) in FEATURE.iter().zip(quantization.dequantize_u8(&codes)) {
+            assert!((x - y).abs() <= quantization.scale / 2.0 + 1e-12);
+        }
+    }
+
+    #[test]
+    fn f16_round_trip() {
+        let quantization = Quantization::fit(0.0, 31.0);
+        assert_eq!(
+            quantization.saturated_count(&FEATURE, QuantizedFormat::F16),
+            0
+        );
+        let codes = quantization.quantize_f16(&FEATURE);
+        let dequantized = quantization.dequantize(&QuantizedFeature::F16(codes));
+        for (x, y) in FEATURE.iter().zip(dequantized) {
+            // half precision keeps 11 significant bits
+            assert!((x - y).abs() <= x.abs() / 2048.0 + 1e-12, "{} != {}", x, y);
+        }
+    }
+
+    #[test]
+    fn out_of_range_values_saturate() {
+        let quantization = Quantization::fit(1.0, 2.0);
+        let feature = [-5.0, 0.99, 1.0, 2.0, 2.01, 1e9];
+        assert_eq!(
+            quantization.saturated_count(&feature, QuantizedFormat::U8),
+            4
+        );
+        assert_eq!(
+            quantization.quantize_u8(&feature),
+            [0, 0, 0, u8::MAX, u8::MAX, u8::MAX]
+        );
+        // f16 reaches far beyond the fitted range, but not to infinity
+        assert_eq!(
+            quantization.saturated_count(&feature, QuantizedFormat::F16),
+            1
+        );
+        let codes = quantization.quantize_f16(&feature);
+        assert_eq!(codes[5], f16::MAX.to_bits());
+        assert!(codes.iter().all(|&code| f16::from_bits(code).is_finite()));
+        let dequantized = quantization.dequantize_f16(&codes);
+        assert!((dequantized[0] - -5.0).abs() < 1e-2);
+        assert!((dequantized[4] - 2.01).abs() < 1e-3);
+    }
+
+    #[test]
+    fn constant_features() {
+        // a column where every glyph has the same value
+        let quantization = Quantization::fit(0.5, 0.5);
+        assert_eq!(
+            quantization,
+            Quantization {
+                scale: 1.0,
+                offset: 0.5
+            }
+        );
+        let feature = [0.5; 4];
+        assert_eq!(quantization.quantize_u8(&feature), [0; 4]);
+        assert_eq!(
+            quantization.dequantize_u8(&quantization.quantize_u8(&feature)),
+            feature
+        );
+        assert_eq!(
+            quantization.dequantize_f16(&quantization.quantize_f16(&feature)),
+            feature
+        );
+    }
+}
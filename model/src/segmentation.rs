@@ -0,0 +1,266 @@
+use std::f64::consts::PI;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    geometry::{distance, turn},
+    stroke::{PenPoint, PenStroke, Stroke},
+};
+
+/// Re-segmentation of the input into strokes, so that the same shape gives the same strokes
+/// however it was drawn.
+///
+/// First, consecutive strokes are joined where one ends close to where the next one starts
+/// and the pen keeps its direction across the gap (a stroke broken by lifting the pen). Then
+/// strokes are split at sharp corners (strokes connected by writing semi-cursively, or a bend
+/// such as the top right of 口). Lengths are in units of the 200x200 frame (see
+/// [`crate::CanvasSpec`]) and angles are in radians.
+///
+/// With the time data of [`PenStroke`]s and a `max_merge_pause` (as in [`Self::PEN_AWARE`]),
+/// strokes separated by a long pause are never joined.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SegmentationParams {
+    /// Tolerance of the corner detection (see [`Stroke::simplified_points`]).
+    pub corner_tolerance: f64,
+    /// Strokes are split at corners turning by more than this.
+    pub split_angle: f64,
+    /// Corners are only split if both pieces next to them are at least this long, so that
+    /// hooks stay attached.
+    pub min_piece_length: f64,
+    /// Largest gap between the end of a stroke and the start of the next one to join them.
+    pub merge_distance: f64,
+    /// Largest change of direction across a gap to join the strokes around it.
+    pub merge_angle: f64,
+    /// With time data, the longest pause (in milliseconds) between the end of a stroke and the
+    /// start of the next one to join them. Zero disables the check.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub max_merge_pause: f64,
+}
+
+impl SegmentationParams {
+    pub const DEFAULT: SegmentationParams = SegmentationParams {
+        corner_tolerance: 4.0,
+        split_angle: 5.0 * PI / 12.0,
+        min_piece_length: 25.0,
+        merge_distance: 12.0,
+        merge_angle: PI / 6.0,
+        max_merge_pause: 0.0,
+    };
+
+    /// [`Self::DEFAULT`] with the pause check using the time data.
+    pub const PEN_AWARE: SegmentationParams = SegmentationParams {
+        max_merge_pause: 150.0,
+        ..Self::DEFAULT
+    };
+}
+
+impl Default for SegmentationParams {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Length of the end of a stroke its direction is measured over.
+const DIRECTION_LENGTH: f64 = 10.0;
+
+/// Joins broken strokes and splits connected ones (see [`SegmentationParams`]). Strokes are
+/// never emptied.
+pub fn segment_strokes(strokes: &[Stroke], params: &SegmentationParams) -> Vec<Stroke> {
+    let strokes: Vec<_> = strokes.iter().map(PenStroke::from).collect();
+    segment_pen_strokes(&strokes, params)
+        .iter()
+        .map(Stroke::from)
+        .collect()
+}
+
+/// Same as [`segment_strokes`] for pen strokes.
+pub fn segment_pen_strokes(strokes: &[PenStroke], params: &SegmentationParams) -> Vec<PenStroke> {
+    let mut merged: Vec<PenStroke> = vec![];
+    for stroke in strokes {
+        match merged.last_mut() {
+            Some(last) if params.should_merge(last, stroke) => {
+                last.0.extend(stroke.0.iter().cloned());
+            }
+            _ => merged.push(stroke.clone()),
+        }
+    }
+    merged
+        .iter()
+        .flat_map(|stroke| params.split(stroke))
+        .collect()
+}
+
+impl SegmentationParams {
+    fn should_merge(&self, a: &PenStroke, b: &PenStroke) -> bool {
+        let (Some(end), Some(start)) = (a.0.last(), b.0.first()) else {
+            return false;
+        };
+        let gap = distance(end, start);
+        if gap > self.merge_distance {
+            return false;
+        }
+        if let (Some(t0), Some(t1)) = (end.time, start.time)
+            && self.max_merge_pause > 0.0
+            && t1 - t0 > self.max_merge_pause
+        {
+            return false;
+        }
+        let (Some(out), Some(into)) = (end_direction(&a.0), start_direction(&b.0)) else {
+            return false;
+        };
+        if turn(out, into) > self.merge_angle {
+            return false;
+        }
+        // a gap much shorter than the tolerance of the direction has no direction of its own
+        gap < 1.0 || {
+            let across = (start.y - end.y).atan2(start.x - end.x);
+            turn(out, across) <= self.merge_angle && turn(across, into) <= self.merge_angle
+        }
+    }
+
+    fn split(&self, stroke: &PenStroke) -> Vec<PenStroke> {
+        let points = &stroke.0;
+        let corners = Stroke::from(stroke).simplified_indices(self.corner_tolerance, usize::MAX);
+        let piece_length = |i: usize, j: usize| {
+            points[i..=j]
+                .windows(2)
+                .map(|w| distance(&w[0], &w[1]))
+                .sum::<f64>()
+        };
+
+        let mut pieces = vec![];
+        let mut piece_start = 0;
+        for w in corners.windows(3) {
+            let (p, q, r) = (&points[w[0]], &points[w[1]], &points[w[2]]);
+            let a = (q.y - p.y).atan2(q.x - p.x);
+            let b = (r.y - q.y).atan2(r.x - q.x);
+            if turn(a, b) > self.split_angle
+                && piece_length(piece_start, w[1]) >= self.min_piece_length
+                && piece_length(w[1], w[2]) >= self.min_piece_length
+            {
+                pieces.push(PenStroke(points[piece_start..=w[1]].to_vec()));
+                piece_start = w[1];
+            }
+        }
+        pieces.push(PenStroke(points[piece_start..].to_vec()));
+        pieces
+    }
+}
+
+/// Direction of the last [`DIRECTION_LENGTH`] of the polyline, towards its end.
+fn end_direction(points: &[PenPoint]) -> Option<f64> {
+    let end = points.last()?;
+    let from = points
+        .iter()
+        .rev()
+        .find(|p| distance(p, end) >= DIRECTION_LENGTH)
+        .or_else(|| points.first().filter(|p| distance(p, end) > 0.0))?;
+    Some((end.y - from.y).atan2(end.x - from.x))
+}
+
+/// Direction of the first [`DIRECTION_LENGTH`] of the polyline, away from its start.
+fn start_direction(points: &[PenPoint]) -> Option<f64> {
+    let start = points.first()?;
+    let to = points
+        .iter()
+        .find(|p| distance(start, p) >= DIRECTION_LENGTH)
+        .or_else(|| points.last().filter(|p| distance(start, p) > 0.0))?;
+    Some((to.y - start.y).atan2(to.x - start.x))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stroke::Point;
+
+    fn stroke(points: &[(f64, f64)]) -> Stroke {
+        Stroke(points.iter().map(|&(x, y)| Point { x, y }).collect())
+    }
+
+    fn coordinates(strokes: &[Stroke]) -> Vec<Vec<(f64, f64)>> {
+        strokes
+            .iter()
+            .map(|stroke| stroke.0.iter().map(|p| (p.x, p.y)).collect())
+            .collect()
+    }
+
+    #[test]
+    fn sharp_corner_is_split() {
+        // the top and right sides of 口 drawn in one go
+        let strokes = [stroke(&[(40.0, 40.0), (160.0, 40.0), (160.0, 160.0)])];
+        let segmented = segment_strokes(&strokes, &SegmentationParams::DEFAULT);
+        assert_eq!(
+            coordinates(&segmented),
+            [
+                vec![(40.0, 40.0), (160.0, 40.0)],
+                vec![(160.0, 40.0), (160.0, 160.0)]
+            ]
+        );
+        // 亅: the hook is too short to be a piece of its own
+        let hook = [stroke(&[(100.0, 20.0), (100.0, 170.0), (85.0, 160.0)])];
+        assert_eq!(
+            coordinates(&segment_strokes(&hook, &SegmentationParams::DEFAULT)),
+            coordinates(&hook)
+        );
+    }
+
+    #[test]
+    fn broken_stroke_is_joined() {
+        let strokes = [
+            stroke(&[(20.0, 100.0), (90.0, 100.0)]),
+            stroke(&[(95.0, 101.0), (180.0, 100.0)]),
+        ];
+        let segmented = segment_strokes(&strokes, &SegmentationParams::DEFAULT);
+        assert_eq!(
+            coordinates(&segmented),
+            [vec![
+                (20.0, 100.0),
+                (90.0, 100.0),
+                (95.0, 101.0),
+                (180.0, 100.0)
+            ]]
+        );
+        // 十: the second stroke starts far away and in another direction
+        let cross = [
+            stroke(&[(20.0, 100.0), (180.0, 100.0)]),
+            stroke(&[(100.0, 20.0), (100.0, 180.0)]),
+        ];
+        assert_eq!(
+            segment_strokes(&cross, &SegmentationParams::DEFAULT).len(),
+            2
+        );
+    }
+
+    #[test]
+    fn long_pause_keeps_strokes_apart() {
+        let strokes: Vec<_> = [
+            [(20.0, 100.0, 0.0), (90.0, 100.0, 100.0)],
+            [(95.0, 100.0, 400.0), (180.0, 100.0, 500.0)],
+        ]
+        .iter()
+        .map(|points| {
+            PenStroke(
+                points
+                    .iter()
+                    .map(|&(x, y, time)| PenPoint {
+                        x,
+                        y,
+                        pressure: None,
+                        time: Some(time),
+                    })
+                    .collect(),
+            )
+        })
+        .collect();
+        assert_eq!(
+            segment_pen_strokes(&strokes, &SegmentationParams::DEFAULT).len(),
+            1
+        );
+        assert_eq!(
+            segment_pen_strokes(&strokes, &SegmentationParams::PEN_AWARE).len(),
+            2
+        );
+    }
+}
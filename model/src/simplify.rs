@@ -0,0 +1,103 @@
+use crate::stroke::{Point, Stroke};
+
+impl Stroke {
+    /// Simplifies the stroke into at most `max_points` corner points (including the start and
+    /// end points) by the Ramer–Douglas–Peucker algorithm.
+    ///
+    /// Points are added in the order of their distance from the current polyline, so the most
+    /// significant corners are kept when the limit is reached. Points within `tolerance` of the
+    /// polyline are never added.
+    pub fn simplified_points(&self, tolerance: f64, max_points: usize) -> Vec<Point> {
+        self.simplified_indices(tolerance, max_points)
+            .into_iter()
+            .map(|i| self.0[i].clone())
+            .collect()
+    }
+
+    /// Same as [`Self::simplified_points`], returning the indices of the kept points.
+    pub(crate) fn simplified_indices(&self, tolerance: f64, max_points: usize) -> Vec<usize> {
+        let points = &self.0;
+        if points.is_empty() {
+            return vec![];
+        }
+        if points.len() == 1 || max_points < 2 {
+            return vec![0, points.len() - 1];
+        }
+
+        // indices into `points` of the kept points, in order
+        let mut kept = vec![0, points.len() - 1];
+        while kept.len() < max_points {
+            let farthest = kept
+                .windows(2)
+                .enumerate()
+                .filter_map(|(k, w)| {
+                    let (a, b) = (&points[w[0]], &points[w[1]]);
+                    (w[0] + 1..w[1])
+                        .map(|i| (k, i, distance_from_segment(&points[i], (a, b))))
+                        .max_by(|(_, _, d1), (_, _, d2)| d1.total_cmp(d2))
+                })
+                .max_by(|(_, _, d1), (_, _, d2)| d1.total_cmp(d2));
+            match farthest {
+                Some((k, i, distance)) if distance > tolerance => kept.insert(k + 1, i),
+                _ => break,
+            }
+        }
+        kept
+    }
+}
+
+pub(crate) fn distance_from_segment(p: &Point, (a, b): (&Point, &Point)) -> f64 {
+    let (dx, dy) = (b.x - a.x, b.y - a.y);
+    let len_sq = dx * dx + dy * dy;
+    let t = if len_sq == 0.0 {
+        0.0
+    } else {
+        (((p.x - a.x) * dx + (p.y - a.y) * dy) / len_sq).clamp(0.0, 1.0)
+    };
+    (p.x - (a.x + t * dx)).hypot(p.y - (a.y + t * dy))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::extractor::get_extractor;
+    use crate::stroke::{Point, Stroke};
+
+    fn stroke(points: &[(f64, f64)]) -> Stroke {
+        Stroke(points.iter().map(|&(x, y)| Point { x, y }).collect())
+    }
+
+    #[test]
+    fn collinear_points_collapse() {
+        let line = stroke(&[
+            (0.0, 0.0),
+            (10.0, 5.0),
+            (20.0, 10.0),
+            (30.0, 15.0),
+            (40.0, 20.0),
+        ]);
+        assert_eq!(line.simplified_indices(0.5, usize::MAX), [0, 4]);
+        // v6 summarizes strokes by their simplified points
+        let v6 = get_extractor("6").unwrap();
+        let two_points = stroke(&[(0.0, 0.0), (40.0, 20.0)]);
+        assert_eq!(
+            v6.strokes_to_feature_array(&[line]),
+            v6.strokes_to_feature_array(&[two_points])
+        );
+    }
+
+    #[test]
+    fn corners_are_kept_by_significance() {
+        // an L with a small bump on its long side
+        let l = stroke(&[
+            (0.0, 0.0),
+            (50.0, 2.0),
+            (100.0, 0.0),
+            (100.0, 30.0),
+            (100.0, 60.0),
+        ]);
+        assert_eq!(l.simplified_indices(0.5, usize::MAX), [0, 1, 2, 4]);
+        assert_eq!(l.simplified_indices(0.5, 3), [0, 2, 4]);
+        assert_eq!(l.simplified_indices(5.0, usize::MAX), [0, 2, 4]);
+        assert_eq!(stroke(&[(1.0, 1.0)]).simplified_indices(0.5, 5), [0, 0]);
+    }
+}
@@ -1,4 +1,12 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::error::ModelError;
+
+/// A point, serialized as an `[x, y]` array.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(from = "[f64; 2]", into = "[f64; 2]"))]
 pub struct Point {
     pub x: f64,
     pub y: f64,
@@ -10,11 +18,169 @@ impl From<(f64, f64)> for Point {
     }
 }
 
+impl From<[f64; 2]> for Point {
+    fn from([x, y]: [f64; 2]) -> Self {
+        Self { x, y }
+    }
+}
+
+impl From<Point> for [f64; 2] {
+    fn from(p: Point) -> Self {
+        [p.x, p.y]
+    }
+}
+
+/// A polyline drawn in one go, serialized as an array of points.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
 pub struct Stroke(pub Vec<Point>);
 
+/// Checks that every stroke can be summarized: it has at least one point and all the
+/// coordinates are finite.
+pub fn check_strokes(strokes: &[Stroke]) -> Result<(), ModelError> {
+    for (stroke_index, stroke) in strokes.iter().enumerate() {
+        check_stroke(stroke, stroke_index)?;
+    }
+    Ok(())
+}
+
+/// Same as [`check_strokes`] for a single stroke, reported as the `stroke_index`-th stroke.
+pub(crate) fn check_stroke(stroke: &Stroke, stroke_index: usize) -> Result<(), ModelError> {
+    if stroke.0.is_empty() {
+        return Err(ModelError::EmptyStroke {
+            stroke: stroke_index,
+        });
+    }
+    if let Some(point_index) = stroke
+        .0
+        .iter()
+        .position(|p| !p.x.is_finite() || !p.y.is_finite())
+    {
+        return Err(ModelError::NonFiniteCoordinate {
+            stroke: stroke_index,
+            point: point_index,
+        });
+    }
+    Ok(())
+}
+
+/// A point of pen input, with the pressure and time the device reports along with the position
+/// where available.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PenPoint {
+    pub x: f64,
+    pub y: f64,
+    /// Pressure from 0 to 1, as in pointer events. Leave it out for devices that do not sense
+    /// it, such as mice, whose events report a constant 0.5.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub pressure: Option<f64>,
+    /// Time in milliseconds, from any origin shared by the strokes of a drawing.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub time: Option<f64>,
+}
+
+impl PenPoint {
+    pub fn point(&self) -> Point {
+        Point {
+            x: self.x,
+            y: self.y,
+        }
+    }
+}
+
+impl From<Point> for PenPoint {
+    fn from(Point { x, y }: Point) -> Self {
+        Self {
+            x,
+            y,
+            pressure: None,
+            time: None,
+        }
+    }
+}
+
+/// A [`Stroke`] of pen input. The preprocessing of some models makes use of the pen data;
+/// everything else only sees the positions.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct PenStroke(pub Vec<PenPoint>);
+
+impl From<&Stroke> for PenStroke {
+    fn from(stroke: &Stroke) -> Self {
+        PenStroke(stroke.0.iter().cloned().map(PenPoint::from).collect())
+    }
+}
+
+impl From<&PenStroke> for Stroke {
+    fn from(stroke: &PenStroke) -> Self {
+        Stroke(stroke.0.iter().map(PenPoint::point).collect())
+    }
+}
+
+/// Same as [`check_strokes`] for pen strokes, also checking that the pen data is finite.
+pub fn check_pen_strokes(strokes: &[PenStroke]) -> Result<(), ModelError> {
+    for (stroke_index, stroke) in strokes.iter().enumerate() {
+        check_stroke(&Stroke::from(stroke), stroke_index)?;
+        let is_finite = |value: Option<f64>| value.is_none_or(f64::is_finite);
+        if let Some(point_index) = stroke
+            .0
+            .iter()
+            .position(|p| !is_finite(p.pressure) || !is_finite(p.time))
+        {
+            return Err(ModelError::NonFinitePenData {
+                stroke: stroke_index,
+                point: point_index,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// How the distance of a point from the chord (the line through the start and end points of a
+/// stroke) is measured when looking for the mid point.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ChordDistance {
+    /// `|ax + by + c| / (a^2 + b^2)`, as used up to model v4. This is the perpendicular
+    /// distance divided by the length of the chord, so the threshold depends on the length.
+    #[default]
+    Legacy,
+    /// The perpendicular distance `|ax + by + c| / sqrt(a^2 + b^2)`.
+    Perpendicular,
+}
+
 impl Stroke {
+    /// Summarizes the stroke into its start, mid and end points.
+    ///
+    /// Panics if the stroke is empty or has a NaN coordinate; see [`check_strokes`].
     pub fn summary_points(&self) -> (Point, Point, Point) {
+        self.summary_points_with_threshold(7.0)
+    }
+
+    /// Summarizes the stroke into its start, mid and end points. The point farthest from the
+    /// chord is taken as the mid point if it is farther than `mid_point_threshold`; otherwise
+    /// the middle of the chord is used.
+    pub fn summary_points_with_threshold(&self, mid_point_threshold: f64) -> (Point, Point, Point) {
+        self.summary_points_by(mid_point_threshold, ChordDistance::Legacy)
+    }
+
+    /// Same as [`Self::summary_points_with_threshold`], with the distance from the chord measured
+    /// as specified by `chord_distance`.
+    pub fn summary_points_by(
+        &self,
+        mid_point_threshold: f64,
+        chord_distance: ChordDistance,
+    ) -> (Point, Point, Point) {
         let start = self.0.first().unwrap();
         let end = self.0.last().unwrap();
 
@@ -22,7 +188,10 @@ impl Stroke {
             let a = start.y - end.y;
             let b = -(start.x - end.x);
             let c = start.x * end.y - start.y * end.x;
-            let z = a * a + b * b;
+            let z = match chord_distance {
+                ChordDistance::Legacy => a * a + b * b,
+                ChordDistance::Perpendicular => a.hypot(b),
+            };
             move |p: &Point| {
                 if z == 0.0 {
                     let dx = p.x - start.x;
@@ -41,7 +210,7 @@ impl Stroke {
             .max_by(|(_, d1), (_, d2)| d1.partial_cmp(d2).unwrap())
             .unwrap();
 
-        let mid = if mid_distance > 7.0 {
+        let mid = if mid_distance > mid_point_threshold {
             mid.clone()
         } else {
             Point {
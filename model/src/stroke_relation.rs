@@ -0,0 +1,339 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    canvas::CanvasSpec,
+    geometry::distance,
+    indexed_feature::{Feature, PositionDim},
+    model::StrokeBlock,
+    simplify::distance_from_segment,
+    stroke::{Point, Stroke},
+};
+
+/// Configuration of the positional block of relations between strokes: crossings, junctions
+/// (an end of a stroke touching another stroke), near-parallel pairs and enclosed regions.
+///
+/// Each kind of relation has its own grid over the frame, onto which the places of the
+/// relations are soft-binned; the grids are concatenated in that order. Distances are in units
+/// of the 200x200 frame.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct StrokeRelationParams {
+    /// Value each relation adds at its place.
+    pub weight: f64,
+    pub n_x: usize,
+    pub n_y: usize,
+    /// Distance within which the end of a stroke touches another stroke. Gaps narrower than
+    /// this also close a region.
+    pub touch_distance: f64,
+    /// Largest angle (in radians) between the chords of near-parallel strokes.
+    pub parallel_angle: f64,
+    /// Largest distance between the chords of near-parallel strokes.
+    pub parallel_distance: f64,
+}
+
+const N_RELATION_KINDS: usize = 4;
+const CROSSING: usize = 0;
+const JUNCTION: usize = 1;
+const PARALLEL: usize = 2;
+const ENCLOSURE: usize = 3;
+
+/// Cells on each side of the raster the enclosed regions are looked for in.
+const ENCLOSURE_GRID: usize = 32;
+/// Regions of fewer cells are gaps between thick lines rather than enclosures.
+const MIN_ENCLOSURE_CELLS: usize = 4;
+
+impl StrokeBlock for StrokeRelationParams {
+    fn colsize(&self) -> usize {
+        N_RELATION_KINDS * PositionDim(self.n_x, self.n_y).colsize()
+    }
+
+    /// One grid per kind of relation, each holding the places where such relations are found.
+    fn generate_feature_array(&self, strokes: &[Stroke]) -> Vec<f64> {
+        let dim = PositionDim(self.n_x, self.n_y);
+        let mut feature_array = Vec::with_capacity(self.colsize());
+        for places in &self.places(strokes) {
+            let elements: Vec<_> = places
+                .iter()
+                .map(|p| PositionDim::element(p.x, p.y, self.weight))
+                .collect();
+            feature_array.extend(dim.generate_feature_array(&elements));
+        }
+        feature_array
+    }
+}
+
+impl StrokeRelationParams {
+    /// The places of the relations, by kind.
+    fn places(&self, strokes: &[Stroke]) -> [Vec<Point>; N_RELATION_KINDS] {
+        let mut places: [Vec<Point>; N_RELATION_KINDS] = Default::default();
+        for (i, a) in strokes.iter().enumerate() {
+            for b in &strokes[i + 1..] {
+                self.add_pair_relations(a, b, &mut places);
+            }
+        }
+        places[ENCLOSURE] = self.enclosures(strokes);
+        places
+    }
+
+    fn add_pair_relations(&self, a: &Stroke, b: &Stroke, places: &mut [Vec<Point>]) {
+        let ends = |s: &Stroke| [s.0.first().unwrap().clone(), s.0.last().unwrap().clone()];
+        let (a_ends, b_ends) = (ends(a), ends(b));
+        let near_end = |p: &Point| {
+            a_ends
+                .iter()
+                .chain(&b_ends)
+                .any(|e| distance(p, e) < self.touch_distance)
+        };
+
+        // junctions: an end of one stroke on the other one; ends meeting each other (an L-shaped
+        // corner) make a single junction
+        let mut junctions: Vec<Point> = vec![];
+        for (ends, other) in [(&a_ends, b), (&b_ends, a)] {
+            for end in ends {
+                if distance_from_stroke(end, other) < self.touch_distance
+                    && !junctions
+                        .iter()
+                        .any(|j| distance(j, end) < self.touch_distance)
+                {
+                    junctions.push(end.clone());
+                }
+            }
+        }
+
+        // crossings away from the ends, which are junctions instead
+        let mut crossings: Vec<Point> = vec![];
+        for (p, q) in a.0.iter().zip(a.0.iter().skip(1)) {
+            for (r, s) in b.0.iter().zip(b.0.iter().skip(1)) {
+                if let Some(x) = segment_intersection((p, q), (r, s))
+                    && !near_end(&x)
+                    && !crossings
+                        .iter()
+                        .any(|c| distance(c, &x) < self.touch_distance)
+                {
+                    crossings.push(x);
+                }
+            }
+        }
+
+        let parallel = (junctions.is_empty() && crossings.is_empty())
+            .then(|| self.parallel_place((&a_ends[0], &a_ends[1]), (&b_ends[0], &b_ends[1])))
+            .flatten();
+
+        places[CROSSING].extend(crossings);
+        places[JUNCTION].extend(junctions);
+        places[PARALLEL].extend(parallel);
+    }
+
+    /// The middle of the two chords, if they are near-parallel, close to each other and
+    /// overlapping side by side.
+    fn parallel_place(&self, (p, q): (&Point, &Point), (r, s): (&Point, &Point)) -> Option<Point> {
+        let (len_a, len_b) = (distance(p, q), distance(r, s));
+        if len_a < self.touch_distance || len_b < self.touch_distance {
+            return None;
+        }
+        let (ux, uy) = ((q.x - p.x) / len_a, (q.y - p.y) / len_a);
+        let (vx, vy) = ((s.x - r.x) / len_b, (s.y - r.y) / len_b);
+        // angle between the lines, regardless of the drawing direction
+        let cos = (ux * vx + uy * vy).abs().min(1.0);
+        if cos.acos() > self.parallel_angle {
+            return None;
+        }
+
+        // overlap of the projections of the chords onto the first one
+        let project = |t: &Point| (t.x - p.x) * ux + (t.y - p.y) * uy;
+        let (t0, t1) = (project(r), project(s));
+        let (lo, hi) = (t0.min(t1).max(0.0), t0.max(t1).min(len_a));
+        if lo >= hi {
+            return None;
+        }
+        let mid = (lo + hi) / 2.0;
+        let on_a = Point {
+            x: p.x + mid * ux,
+            y: p.y + mid * uy,
+        };
+        // the foot of `on_a` on the line of the second chord
+        let along = (on_a.x - r.x) * vx + (on_a.y - r.y) * vy;
+        let on_b = Point {
+            x: r.x + along * vx,
+            y: r.y + along * vy,
+        };
+        if distance(&on_a, &on_b) > self.parallel_distance {
+            return None;
+        }
+        Some(Point {
+            x: (on_a.x + on_b.x) / 2.0,
+            y: (on_a.y + on_b.y) / 2.0,
+        })
+    }
+
+    /// Centroids of the regions enclosed by the strokes, found by flooding a raster of the frame
+    /// from its border. The strokes are drawn thick enough to close gaps narrower than the touch
+    /// distance.
+    fn enclosures(&self, strokes: &[Stroke]) -> Vec<Point> {
+        let frame = CanvasSpec::GLYPHWIKI;
+        let n = ENCLOSURE_GRID;
+        let cell = frame.width / n as f64;
+        // at least 0.75 cells, so that 4-connected flooding cannot step over a diagonal line
+        let radius = (self.touch_distance / 2.0).max(0.75 * cell);
+        let center = |i: usize, j: usize| Point {
+            x: frame.origin_x + (i as f64 + 0.5) * cell,
+            y: frame.origin_y + (j as f64 + 0.5) * cell,
+        };
+
+        // each segment only marks the cells around it, instead of testing every segment for
+        // every cell
+        let mut ink = vec![false; n * n];
+        // the cells whose centres are within the radius of `min..=max` on an axis, give or take
+        // one so that rounding cannot leave any out
+        let cell_range = |min: f64, max: f64, origin: f64| {
+            let first = ((min - radius - origin) / cell - 0.5).floor().max(0.0) as usize;
+            let last = ((max + radius - origin) / cell - 0.5)
+                .ceil()
+                .min((n - 1) as f64);
+            // empty if the segment is entirely off the frame
+            first..(last + 1.0).max(0.0) as usize
+        };
+        for stroke in strokes {
+            let segments = match &stroke.0[..] {
+                [p] => vec![(p, p)],
+                points => points.windows(2).map(|w| (&w[0], &w[1])).collect(),
+            };
+            for (p, q) in segments {
+                for j in cell_range(p.y.min(q.y), p.y.max(q.y), frame.origin_y) {
+                    for i in cell_range(p.x.min(q.x), p.x.max(q.x), frame.origin_x) {
+                        let k = j * n + i;
+                        if !ink[k] && distance_from_segment(&center(i, j), (p, q)) <= radius {
+                            ink[k] = true;
+                        }
+                    }
+                }
+            }
+        }
+
+        // 0: not visited, 1: outside, 2..: enclosed region
+        let mut label = vec![0usize; n * n];
+        let flood = |start: usize, value: usize, label: &mut [usize]| {
+            let mut cells = vec![];
+            let mut stack = vec![start];
+            label[start] = value;
+            while let Some(k) = stack.pop() {
+                cells.push(k);
+                let (i, j) = (k % n, k / n);
+                let neighbors = [
+                    (i > 0).then(|| k - 1),
+                    (i + 1 < n).then(|| k + 1),
+                    (j > 0).then(|| k - n),
+                    (j + 1 < n).then(|| k + n),
+                ];
+                for l in neighbors.into_iter().flatten() {
+                    if !ink[l] && label[l] == 0 {
+                        label[l] = value;
+                        stack.push(l);
+                    }
+                }
+            }
+            cells
+        };
+        for k in 0..n * n {
+            let (i, j) = (k % n, k / n);
+            let border = i == 0 || j == 0 || i == n - 1 || j == n - 1;
+            if border && !ink[k] && label[k] == 0 {
+                flood(k, 1, &mut label);
+            }
+        }
+
+        let mut places = vec![];
+        for k in 0..n * n {
+            if ink[k] || label[k] != 0 {
+                continue;
+            }
+            let cells = flood(k, 2 + places.len(), &mut label);
+            if cells.len() < MIN_ENCLOSURE_CELLS {
+                continue;
+            }
+            let (sx, sy) = cells.iter().fold((0.0, 0.0), |(sx, sy), &k| {
+                let c = center(k % n, k / n);
+                (sx + c.x, sy + c.y)
+            });
+            places.push(Point {
+                x: sx / cells.len() as f64,
+                y: sy / cells.len() as f64,
+            });
+        }
+        places
+    }
+}
+
+fn distance_from_stroke(p: &Point, stroke: &Stroke) -> f64 {
+    match &stroke.0[..] {
+        [q] => distance(p, q),
+        points => points
+            .windows(2)
+            .map(|w| distance_from_segment(p, (&w[0], &w[1])))
+            .fold(f64::INFINITY, f64::min),
+    }
+}
+
+/// The point where the two segments cross, if they do.
+fn segment_intersection((p, q): (&Point, &Point), (r, s): (&Point, &Point)) -> Option<Point> {
+    let (dx1, dy1) = (q.x - p.x, q.y - p.y);
+    let (dx2, dy2) = (s.x - r.x, s.y - r.y);
+    let denom = dx1 * dy2 - dy1 * dx2;
+    if denom == 0.0 {
+        return None;
+    }
+    let t = ((r.x - p.x) * dy2 - (r.y - p.y) * dx2) / denom;
+    let u = ((r.x - p.x) * dy1 - (r.y - p.y) * dx1) / denom;
+    ((0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u)).then_some(Point {
+        x: p.x + t * dx1,
+        y: p.y + t * dy1,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::params::ModelParams;
+
+    fn stroke(points: &[(f64, f64)]) -> Stroke {
+        Stroke(points.iter().map(|&(x, y)| Point { x, y }).collect())
+    }
+
+    fn counts(strokes: &[Stroke]) -> [usize; N_RELATION_KINDS] {
+        let params = ModelParams::V10.stroke_relations.unwrap();
+        params.places(strokes).map(|places| places.len())
+    }
+
+    #[test]
+    fn crossing_and_junction_are_told_apart() {
+        let horizontal = stroke(&[(20.0, 100.0), (180.0, 100.0)]);
+        // 十
+        let params = ModelParams::V10.stroke_relations.unwrap();
+        let places = params.places(&[horizontal.clone(), stroke(&[(100.0, 20.0), (100.0, 180.0)])]);
+        assert_eq!(places.each_ref().map(Vec::len), [1, 0, 0, 0]);
+        let crossing = &places[CROSSING][0];
+        assert_eq!((crossing.x, crossing.y), (100.0, 100.0));
+        // 丁: the vertical stroke starts on the horizontal one
+        let junction = counts(&[horizontal, stroke(&[(100.0, 100.0), (100.0, 180.0)])]);
+        assert_eq!(junction, [0, 1, 0, 0]);
+    }
+
+    #[test]
+    fn parallel_strokes_and_enclosures() {
+        // 二
+        let parallel = counts(&[
+            stroke(&[(40.0, 75.0), (160.0, 75.0)]),
+            stroke(&[(20.0, 125.0), (180.0, 125.0)]),
+        ]);
+        assert_eq!(parallel, [0, 0, 1, 0]);
+        // 口 drawn as three strokes; the corners are junctions
+        let enclosure = counts(&[
+            stroke(&[(40.0, 40.0), (40.0, 160.0)]),
+            stroke(&[(40.0, 40.0), (160.0, 40.0), (160.0, 160.0)]),
+            stroke(&[(40.0, 160.0), (160.0, 160.0)]),
+        ]);
+        assert_eq!(enclosure[CROSSING], 0);
+        assert_eq!(enclosure[ENCLOSURE], 1);
+    }
+}
@@ -0,0 +1,248 @@
+use std::f64::consts::PI;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    canvas::CanvasSpec,
+    geometry::{BoundingBox, distance, turn},
+    indexed_feature::{Feature, PositionDim},
+    model::StrokeBlock,
+    stroke::{Point, Stroke},
+};
+
+/// Shape class of a single stroke, after the basic strokes of CJK calligraphy.
+///
+/// Lines between the horizontal and vertical directions are classified by their orientation
+/// only (`/` is left-falling and `\` is right-falling), so that a stroke drawn backwards or a
+/// rising stroke still falls into one of the classes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum StrokeType {
+    Horizontal,
+    Vertical,
+    LeftFalling,
+    RightFalling,
+    /// A stroke too short to have a meaningful direction.
+    Dot,
+    /// A long piece ending in a short piece that turns sharply.
+    Hook,
+    /// Two pieces of comparable length meeting at a corner.
+    Bend,
+    /// Three or more pieces.
+    Compound,
+}
+
+impl StrokeType {
+    /// All the types, in the order of their blocks in the histogram.
+    pub const ALL: [StrokeType; 8] = [
+        StrokeType::Horizontal,
+        StrokeType::Vertical,
+        StrokeType::LeftFalling,
+        StrokeType::RightFalling,
+        StrokeType::Dot,
+        StrokeType::Hook,
+        StrokeType::Bend,
+        StrokeType::Compound,
+    ];
+
+    fn block_index(self) -> usize {
+        Self::ALL.iter().position(|&t| t == self).unwrap()
+    }
+}
+
+/// Strokes whose bounding box has a diagonal shorter than this (in the 200x200 frame) are dots.
+const DOT_SIZE: f64 = 0.12 * CanvasSpec::GLYPHWIKI.width;
+/// Tolerance of the corner detection, relative to the size of the stroke.
+const CORNER_TOLERANCE: f64 = 0.1;
+/// Corners turning less than this are part of a smooth curve.
+const CORNER_ANGLE: f64 = PI / 4.0;
+/// Lines within this angle of the horizontal or vertical axis are horizontal or vertical.
+const AXIS_ANGLE: f64 = PI / 9.0;
+/// A last piece shorter than this fraction of the previous one makes a hook.
+const HOOK_RATIO: f64 = 0.35;
+
+impl Stroke {
+    /// Classifies the stroke by its shape. Coordinates are expected in the
+    /// [`CanvasSpec::GLYPHWIKI`] frame (y pointing down), which the size of a dot is relative to.
+    pub fn stroke_type(&self) -> StrokeType {
+        let points = &self.0;
+        let (Some(start), Some(end), Some(bbox)) =
+            (points.first(), points.last(), BoundingBox::of(points))
+        else {
+            return StrokeType::Dot;
+        };
+        let size = bbox.width().hypot(bbox.height());
+        if size < DOT_SIZE {
+            return StrokeType::Dot;
+        }
+
+        let corners = smooth_corners(self.simplified_points(CORNER_TOLERANCE * size, 6));
+        match &corners[..] {
+            [p, q, r] => {
+                if distance(q, r) < HOOK_RATIO * distance(p, q) {
+                    StrokeType::Hook
+                } else {
+                    StrokeType::Bend
+                }
+            }
+            [_, _, _, _, ..] => StrokeType::Compound,
+            _ => line_type(start, end),
+        }
+    }
+}
+
+/// Drops the corners that turn less than [`CORNER_ANGLE`], so that a curve sampled into several
+/// points is a single piece.
+fn smooth_corners(mut corners: Vec<Point>) -> Vec<Point> {
+    let mut i = 1;
+    while i + 1 < corners.len() {
+        let (p, q, r) = (&corners[i - 1], &corners[i], &corners[i + 1]);
+        let a = (q.y - p.y).atan2(q.x - p.x);
+        let b = (r.y - q.y).atan2(r.x - q.x);
+        if turn(a, b) < CORNER_ANGLE {
+            corners.remove(i);
+        } else {
+            i += 1;
+        }
+    }
+    corners
+}
+
+fn line_type(start: &Point, end: &Point) -> StrokeType {
+    let (dx, dy) = (end.x - start.x, end.y - start.y);
+    // orientation in [0, PI), measured clockwise from the x axis since y points down
+    let angle = dy.atan2(dx).rem_euclid(PI);
+    if !(AXIS_ANGLE..=PI - AXIS_ANGLE).contains(&angle) {
+        StrokeType::Horizontal
+    } else if (angle - PI / 2.0).abs() < AXIS_ANGLE {
+        StrokeType::Vertical
+    } else if angle > PI / 2.0 {
+        StrokeType::LeftFalling
+    } else {
+        StrokeType::RightFalling
+    }
+}
+
+/// Configuration of the positional histogram of stroke types: for each [`StrokeType`], a grid
+/// over the frame onto which the centers of the strokes of that type are soft-binned. The grids
+/// are concatenated in the order of [`StrokeType::ALL`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct StrokeTypeParams {
+    /// Value each stroke adds at its center.
+    pub weight: f64,
+    pub n_x: usize,
+    pub n_y: usize,
+}
+
+impl StrokeBlock for StrokeTypeParams {
+    fn colsize(&self) -> usize {
+        StrokeType::ALL.len() * PositionDim(self.n_x, self.n_y).colsize()
+    }
+
+    /// One grid per [`StrokeType`], each holding the centers of the strokes of that type.
+    fn generate_feature_array(&self, strokes: &[Stroke]) -> Vec<f64> {
+        let mut elements = vec![vec![]; StrokeType::ALL.len()];
+        for stroke in strokes {
+            // checked strokes have points
+            let (cx, cy) = BoundingBox::of(&stroke.0).unwrap().center();
+            elements[stroke.stroke_type().block_index()].push(PositionDim::element(
+                cx,
+                cy,
+                self.weight,
+            ));
+        }
+
+        let dim = PositionDim(self.n_x, self.n_y);
+        let mut feature_array = Vec::with_capacity(self.colsize());
+        for elements in &elements {
+            feature_array.extend(dim.generate_feature_array(elements));
+        }
+        feature_array
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stroke(points: &[(f64, f64)]) -> Stroke {
+        Stroke(points.iter().map(|&(x, y)| Point { x, y }).collect())
+    }
+
+    #[test]
+    fn basic_strokes_are_classified() {
+        let cases = [
+            // 横, slightly rising as in handwriting, and drawn backwards
+            (vec![(20.0, 104.0), (180.0, 98.0)], StrokeType::Horizontal),
+            (vec![(180.0, 100.0), (20.0, 100.0)], StrokeType::Horizontal),
+            // 縦
+            (vec![(100.0, 20.0), (102.0, 180.0)], StrokeType::Vertical),
+            (vec![(150.0, 30.0), (40.0, 170.0)], StrokeType::LeftFalling),
+            (vec![(50.0, 30.0), (170.0, 170.0)], StrokeType::RightFalling),
+            (vec![(100.0, 100.0), (108.0, 106.0)], StrokeType::Dot),
+            // 亅
+            (
+                vec![(100.0, 20.0), (100.0, 170.0), (80.0, 160.0)],
+                StrokeType::Hook,
+            ),
+            // 乚 without the hook
+            (
+                vec![(40.0, 30.0), (40.0, 150.0), (170.0, 150.0)],
+                StrokeType::Bend,
+            ),
+            // 乙-like zigzag
+            (
+                vec![(30.0, 40.0), (170.0, 40.0), (40.0, 170.0), (170.0, 170.0)],
+                StrokeType::Compound,
+            ),
+        ];
+        for (points, expected) in cases {
+            assert_eq!(stroke(&points).stroke_type(), expected, "{points:?}");
+        }
+    }
+
+    #[test]
+    fn sampled_curve_is_a_single_piece() {
+        // 丿 sampled along a bow
+        let points: Vec<_> = (0..=10)
+            .map(|i| {
+                let t = i as f64 / 10.0;
+                let bow = 15.0 * (PI * t).sin();
+                (150.0 - 110.0 * t + bow, 30.0 + 140.0 * t + bow)
+            })
+            .collect();
+        assert_eq!(stroke(&points).stroke_type(), StrokeType::LeftFalling);
+    }
+
+    #[test]
+    fn histogram_counts_each_stroke_once() {
+        let params = StrokeTypeParams {
+            weight: 2.0,
+            n_x: 3,
+            n_y: 3,
+        };
+        let strokes = [
+            stroke(&[(20.0, 100.0), (180.0, 100.0)]),
+            stroke(&[(100.0, 20.0), (100.0, 180.0)]),
+        ];
+        let feature = params.generate_feature_array(&strokes);
+        assert_eq!(feature.len(), params.colsize());
+        let block = |t: StrokeType| {
+            let size = feature.len() / StrokeType::ALL.len();
+            &feature[t.block_index() * size..][..size]
+        };
+        // 十: one horizontal and one vertical stroke, both centered in the middle cell
+        for t in StrokeType::ALL {
+            let block = block(t);
+            match t {
+                StrokeType::Horizontal | StrokeType::Vertical => {
+                    assert_eq!(block[4], params.weight)
+                }
+                _ => assert!(block.iter().all(|&x| x == 0.0), "{t:?}"),
+            }
+        }
+    }
+}
@@ -0,0 +1,681 @@
+//! Reading strokes from the shapes of an SVG document.
+
+use std::fmt;
+
+use roxmltree::{Document, Node};
+
+use crate::canvas::CanvasSpec;
+use crate::curve::{cubic_bezier_stroke, quadratic_bezier_stroke};
+use crate::drawing::Drawing;
+use crate::stroke::{Point, Stroke};
+
+/// The namespace of SVG elements.
+pub const SVG_NAMESPACE: &str = "http://www.w3.org/2000/svg";
+
+/// Invalid SVG input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SvgError {
+    /// The document is not well-formed XML.
+    Xml(String),
+    /// The root element is not `<svg>`.
+    NotSvg,
+    /// The `attribute` of an `<element>` cannot be parsed.
+    InvalidAttribute {
+        element: String,
+        attribute: &'static str,
+    },
+    /// Path data uses a command other than M, L, H, V, C, S, Q, T and Z, such as an arc.
+    UnsupportedPathCommand { command: char },
+}
+
+impl fmt::Display for SvgError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SvgError::Xml(message) => write!(f, "invalid XML: {}", message),
+            SvgError::NotSvg => write!(f, "root element is not <svg>"),
+            SvgError::InvalidAttribute { element, attribute } => {
+                write!(f, "invalid {} attribute of <{}>", attribute, element)
+            }
+            SvgError::UnsupportedPathCommand { command } => {
+                write!(f, "unsupported path command {}", command)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SvgError {}
+
+/// Reads the strokes of an SVG document, on the canvas given by the `viewBox` of the root
+/// element (or its `width` and `height` in user units, or else [`CanvasSpec::GLYPHWIKI`]).
+///
+/// Each subpath of a `<path>`, and each `<polyline>`, `<polygon>` and `<line>`, becomes a
+/// stroke, with the `transform` of the element and its ancestors applied. Curves are flattened
+/// as by [`quadratic_bezier_stroke`] and [`cubic_bezier_stroke`]. Other shapes, and elements
+/// that are not rendered directly such as the contents of `<defs>`, are skipped.
+pub fn drawing_from_svg(svg: &str) -> Result<Drawing, SvgError> {
+    let document = Document::parse(svg).map_err(|err| SvgError::Xml(err.to_string()))?;
+    let root = document.root_element();
+    if !is_svg(root, "svg") {
+        return Err(SvgError::NotSvg);
+    }
+    let canvas = root_canvas(root)?;
+    let mut strokes = vec![];
+    read_shapes(root, Transform::IDENTITY, &mut strokes)?;
+    Ok(Drawing::new(canvas, strokes))
+}
+
+/// The strokes of [`drawing_from_svg`], in the user units of the document.
+pub fn strokes_from_svg(svg: &str) -> Result<Vec<Stroke>, SvgError> {
+    Ok(drawing_from_svg(svg)?.strokes)
+}
+
+fn is_svg(node: Node, name: &str) -> bool {
+    // hand-written snippets often omit the namespace declaration
+    node.tag_name().name() == name
+        && matches!(node.tag_name().namespace(), None | Some(SVG_NAMESPACE))
+}
+
+fn invalid_attribute(node: Node, attribute: &'static str) -> SvgError {
+    SvgError::InvalidAttribute {
+        element: node.tag_name().name().to_string(),
+        attribute,
+    }
+}
+
+fn root_canvas(root: Node) -> Result<CanvasSpec, SvgError> {
+    if let Some(view_box) = root.attribute("viewBox") {
+        let canvas = match parse_numbers(view_box).as_deref() {
+            Some(&[origin_x, origin_y, width, height]) => CanvasSpec {
+                width,
+                height,
+                origin_x,
+                origin_y,
+            },
+            _ => return Err(invalid_attribute(root, "viewBox")),
+        };
+        return canvas
+            .check()
+            .map(|()| canvas)
+            .map_err(|_| invalid_attribute(root, "viewBox"));
+    }
+    // without a viewBox, a size in other units (e.g. `100%` or `10mm`) says nothing about the
+    // user coordinates the shapes are drawn in
+    let size = |attribute| root.attribute(attribute).and_then(parse_length);
+    let (Some(width), Some(height)) = (size("width"), size("height")) else {
+        return Ok(CanvasSpec::GLYPHWIKI);
+    };
+    let canvas = CanvasSpec {
+        width,
+        height,
+        ..CanvasSpec::GLYPHWIKI
+    };
+    canvas
+        .check()
+        .map(|()| canvas)
+        .map_err(|_| invalid_attribute(root, "width"))
+}
+
+/// Elements whose contents are only drawn where they are referenced.
+const NOT_RENDERED: [&str; 6] = ["defs", "clipPath", "mask", "marker", "pattern", "symbol"];
+
+fn read_shapes(
+    parent: Node,
+    transform: Transform,
+    strokes: &mut Vec<Stroke>,
+) -> Result<(), SvgError> {
+    for node in parent.children().filter(|node| node.is_element()) {
+        if NOT_RENDERED.iter().any(|name| is_svg(node, name))
+            || node.attribute("display") == Some("none")
+        {
+            continue;
+        }
+        let transform = match node.attribute("transform") {
+            Some(value) => transform.compose(
+                &parse_transform(value).ok_or_else(|| invalid_attribute(node, "transform"))?,
+            ),
+            None => transform,
+        };
+        let polylines = if is_svg(node, "path") {
+            let Some(data) = node.attribute("d") else {
+                continue;
+            };
+            parse_path(data).map_err(|err| err.unwrap_or_else(|| invalid_attribute(node, "d")))?
+        } else if is_svg(node, "polyline") || is_svg(node, "polygon") {
+            let mut points = node
+                .attribute("points")
+                .and_then(parse_points)
+                .ok_or_else(|| invalid_attribute(node, "points"))?;
+            if is_svg(node, "polygon") && !points.is_empty() {
+                points.push(points[0].clone());
+            }
+            vec![points]
+        } else if is_svg(node, "line") {
+            let coordinate = |attribute| {
+                node.attribute(attribute)
+                    .map_or(Some(0.0), parse_length)
+                    .ok_or_else(|| invalid_attribute(node, attribute))
+            };
+            vec![vec![
+                Point {
+                    x: coordinate("x1")?,
+                    y: coordinate("y1")?,
+                },
+                Point {
+                    x: coordinate("x2")?,
+                    y: coordinate("y2")?,
+                },
+            ]]
+        } else {
+            read_shapes(node, transform, strokes)?;
+            continue;
+        };
+        strokes.extend(
+            polylines
+                .into_iter()
+                .filter(|points| !points.is_empty())
+                .map(|points| Stroke(points.iter().map(|p| transform.apply(p)).collect())),
+        );
+    }
+    Ok(())
+}
+
+/// An affine transform `[a, b, c, d, e, f]`, mapping `(x, y)` to
+/// `(a x + c y + e, b x + d y + f)` as the SVG `matrix()` does.
+#[derive(Debug, Clone, Copy)]
+struct Transform([f64; 6]);
+
+impl Transform {
+    const IDENTITY: Transform = Transform([1.0, 0.0, 0.0, 1.0, 0.0, 0.0]);
+
+    /// The transform applying `inner` first and then `self`.
+    fn compose(&self, inner: &Transform) -> Transform {
+        let [a, b, c, d, e, f] = self.0;
+        let [p, q, r, s, t, u] = inner.0;
+        Transform([
+            a * p + c * q,
+            b * p + d * q,
+            a * r + c * s,
+            b * r + d * s,
+            a * t + c * u + e,
+            b * t + d * u + f,
+        ])
+    }
+
+    fn apply(&self, p: &Point) -> Point {
+        let [a, b, c, d, e, f] = self.0;
+        Point {
+            x: a * p.x + c * p.y + e,
+            y: b * p.x + d * p.y + f,
+        }
+    }
+}
+
+/// Parses a transform list such as `translate(10 20) rotate(45)`.
+fn parse_transform(value: &str) -> Option<Transform> {
+    let mut transform = Transform::IDENTITY;
+    let mut rest = value;
+    loop {
+        rest = rest.trim_start_matches(|c: char| c.is_ascii_whitespace() || c == ',');
+        if rest.is_empty() {
+            return Some(transform);
+        }
+        let (name, args) = rest.split_once('(')?;
+        let (args, after) = args.split_once(')')?;
+        rest = after;
+        let args = parse_numbers(args)?;
+        let matrix = match (name.trim(), args.as_slice()) {
+            ("matrix", &[a, b, c, d, e, f]) => [a, b, c, d, e, f],
+            ("translate", &[tx]) => [1.0, 0.0, 0.0, 1.0, tx, 0.0],
+            ("translate", &[tx, ty]) => [1.0, 0.0, 0.0, 1.0, tx, ty],
+            ("scale", &[s]) => [s, 0.0, 0.0, s, 0.0, 0.0],
+            ("scale", &[sx, sy]) => [sx, 0.0, 0.0, sy, 0.0, 0.0],
+            ("rotate", &[angle]) => rotation(angle),
+            ("rotate", &[angle, cx, cy]) => {
+                let Transform(matrix) = Transform([1.0, 0.0, 0.0, 1.0, cx, cy])
+                    .compose(&Transform(rotation(angle)))
+                    .compose(&Transform([1.0, 0.0, 0.0, 1.0, -cx, -cy]));
+                matrix
+            }
+            ("skewX", &[angle]) => [1.0, 0.0, angle.to_radians().tan(), 1.0, 0.0, 0.0],
+            ("skewY", &[angle]) => [1.0, angle.to_radians().tan(), 0.0, 1.0, 0.0, 0.0],
+            _ => return None,
+        };
+        transform = transform.compose(&Transform(matrix));
+    }
+}
+
+fn rotation(degrees: f64) -> [f64; 6] {
+    let (sin, cos) = degrees.to_radians().sin_cos();
+    [cos, sin, -sin, cos, 0.0, 0.0]
+}
+
+/// Parses `"x1,y1 x2,y2 ..."` of a `<polyline>` or `<polygon>`.
+fn parse_points(value: &str) -> Option<Vec<Point>> {
+    let numbers = parse_numbers(value)?;
+    if numbers.len() % 2 != 0 {
+        return None;
+    }
+    Some(
+        numbers
+            .chunks_exact(2)
+            .map(|xy| Point { x: xy[0], y: xy[1] })
+            .collect(),
+    )
+}
+
+/// Parses a length in user units (px), or returns `None` for other units.
+fn parse_length(value: &str) -> Option<f64> {
+    let value = value.trim();
+    value.strip_suffix("px").unwrap_or(value).parse().ok()
+}
+
+/// Parses a list of numbers separated by whitespace or commas.
+fn parse_numbers(value: &str) -> Option<Vec<f64>> {
+    let mut scanner = Scanner::new(value);
+    let mut numbers = vec![];
+    while !scanner.at_end() {
+        numbers.push(scanner.number()?);
+    }
+    Some(numbers)
+}
+
+/// Reads the numbers of SVG attribute values, which need no separator when the next number
+/// starts with a sign or a second decimal point, as in `10-5` or `1.5.5`.
+struct Scanner<'a> {
+    text: &'a str,
+    i: usize,
+}
+
+impl<'a> Scanner<'a> {
+    fn new(text: &'a str) -> Self {
+        Self { text, i: 0 }
+    }
+
+    fn skip_separators(&mut self) {
+        let bytes = self.text.as_bytes();
+        while self.i < bytes.len() && (bytes[self.i].is_ascii_whitespace() || bytes[self.i] == b',')
+        {
+            self.i += 1;
+        }
+    }
+
+    fn at_end(&mut self) -> bool {
+        self.skip_separators();
+        self.i == self.text.len()
+    }
+
+    /// The next path command letter, if the next token is one.
+    fn command(&mut self) -> Option<char> {
+        self.skip_separators();
+        let c = *self.text.as_bytes().get(self.i)?;
+        c.is_ascii_alphabetic().then(|| {
+            self.i += 1;
+            c as char
+        })
+    }
+
+    fn number(&mut self) -> Option<f64> {
+        self.skip_separators();
+        let bytes = self.text.as_bytes();
+        let start = self.i;
+        let mut i = self.i;
+        let skip_digits = |i: &mut usize| {
+            while *i < bytes.len() && bytes[*i].is_ascii_digit() {
+                *i += 1;
+            }
+        };
+        if i < bytes.len() && matches!(bytes[i], b'-' | b'+') {
+            i += 1;
+        }
+        let digits = i;
+        skip_digits(&mut i);
+        if i < bytes.len() && bytes[i] == b'.' {
+            i += 1;
+            skip_digits(&mut i);
+        }
+        if !bytes[digits..i].iter().any(u8::is_ascii_digit) {
+            return None;
+        }
+        // an exponent only if digits follow, so that `1e` is not taken for one
+        if i < bytes.len() && matches!(bytes[i], b'e' | b'E') {
+            let mut j = i + 1;
+            if j < bytes.len() && matches!(bytes[j], b'-' | b'+') {
+                j += 1;
+            }
+            if j < bytes.len() && bytes[j].is_ascii_digit() {
+                i = j;
+                skip_digits(&mut i);
+            }
+        }
+        self.i = i;
+        self.text[start..i].parse().ok()
+    }
+
+    fn point(&mut self) -> Option<Point> {
+        let x = self.number()?;
+        let y = self.number()?;
+        Some(Point { x, y })
+    }
+}
+
+/// Parses path data into the points of its subpaths. Subpaths with a single point are
+/// dropped, as they draw nothing.
+///
+/// Fails with `None` on malformed data, to be reported as an invalid `d` attribute.
+fn parse_path(data: &str) -> Result<Vec<Vec<Point>>, Option<SvgError>> {
+    let mut scanner = Scanner::new(data);
+    let mut subpaths = vec![];
+    let mut points: Vec<Point> = vec![];
+    let mut position = Point { x: 0.0, y: 0.0 };
+    let mut start = position.clone();
+    // the command to repeat when its arguments are given again without the letter
+    let mut command = None;
+    // the second control point of the last cubic curve or the control point of the last
+    // quadratic curve, for the smooth curves that reflect it
+    let mut last_cubic_control = None;
+    let mut last_quadratic_control = None;
+
+    let finish = |points: &mut Vec<Point>, subpaths: &mut Vec<Vec<Point>>| {
+        if points.len() > 1 {
+            subpaths.push(std::mem::take(points));
+        }
+        points.clear();
+    };
+
+    while !scanner.at_end() {
+        let letter = match scanner.command() {
+            Some(letter) => letter,
+            None => match command {
+                // arguments following a moveto are linetos
+                Some('M') => 'L',
+                Some('m') => 'l',
+                Some('Z' | 'z') | None => return Err(None),
+                Some(letter) => letter,
+            },
+        };
+        command = Some(letter);
+        let relative = letter.is_ascii_lowercase();
+        let origin = if relative {
+            position.clone()
+        } else {
+            Point { x: 0.0, y: 0.0 }
+        };
+        let offset = |p: Point| Point {
+            x: p.x + origin.x,
+            y: p.y + origin.y,
+        };
+        if points.is_empty() && !matches!(letter, 'M' | 'm' | 'Z' | 'z') {
+            // drawing on after a closepath starts a new subpath at the same point
+            points.push(position.clone());
+        }
+        let mut cubic_control = None;
+        let mut quadratic_control = None;
+        match letter.to_ascii_uppercase() {
+            'M' => {
+                finish(&mut points, &mut subpaths);
+                position = offset(scanner.point().ok_or(None)?);
+                start = position.clone();
+                points.push(position.clone());
+            }
+            'L' => {
+                position = offset(scanner.point().ok_or(None)?);
+                points.push(position.clone());
+            }
+            'H' => {
+                position.x = scanner.number().ok_or(None)? + origin.x;
+                points.push(position.clone());
+            }
+            'V' => {
+                position.y = scanner.number().ok_or(None)? + origin.y;
+                points.push(position.clone());
+            }
+            'C' | 'S' => {
+                let control1 = if letter.eq_ignore_ascii_case(&'C') {
+                    offset(scanner.point().ok_or(None)?)
+                } else {
+                    reflect(last_cubic_control.as_ref(), &position)
+                };
+                let control2 = offset(scanner.point().ok_or(None)?);
+                let end = offset(scanner.point().ok_or(None)?);
+                let Stroke(curve) =
+                    cubic_bezier_stroke(position, control1, control2.clone(), end.clone());
+                points.extend(curve.into_iter().skip(1));
+                position = end;
+                cubic_control = Some(control2);
+            }
+            'Q' | 'T' => {
+                let control = if letter.eq_ignore_ascii_case(&'Q') {
+                    offset(scanner.point().ok_or(None)?)
+                } else {
+                    reflect(last_quadratic_control.as_ref(), &position)
+                };
+                let end = offset(scanner.point().ok_or(None)?);
+                let Stroke(curve) = quadratic_bezier_stroke(position, control.clone(), end.clone());
+                points.extend(curve.into_iter().skip(1));
+                position = end;
+                quadratic_control = Some(control);
+            }
+            'Z' => {
+                points.push(start.clone());
+                finish(&mut points, &mut subpaths);
+                position = start.clone();
+            }
+            _ => return Err(Some(SvgError::UnsupportedPathCommand { command: letter })),
+        }
+        last_cubic_control = cubic_control;
+        last_quadratic_control = quadratic_control;
+    }
+    finish(&mut points, &mut subpaths);
+    Ok(subpaths)
+}
+
+/// The reflection of `control` about `position`, or `position` itself if there is no control
+/// point to reflect.
+fn reflect(control: Option<&Point>, position: &Point) -> Point {
+    match control {
+        Some(control) => Point {
+            x: 2.0 * position.x - control.x,
+            y: 2.0 * position.y - control.y,
+        },
+        None => position.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn coordinates(strokes: &[Stroke]) -> Vec<Vec<(f64, f64)>> {
+        strokes
+            .iter()
+            .map(|stroke| stroke.0.iter().map(|p| (p.x, p.y)).collect())
+            .collect()
+    }
+
+    fn svg(attributes: &str, body: &str) -> String {
+        format!(
+            r#"<svg xmlns="{}" {}>{}</svg>"#,
+            SVG_NAMESPACE, attributes, body
+        )
+    }
+
+    fn path_strokes(d: &str) -> Vec<Vec<(f64, f64)>> {
+        coordinates(&strokes_from_svg(&svg("", &format!(r#"<path d="{}"/>"#, d))).unwrap())
+    }
+
+    fn assert_close(actual: Vec<Vec<(f64, f64)>>, expected: Vec<Vec<(f64, f64)>>) {
+        let close = actual.len() == expected.len()
+            && actual.iter().zip(&expected).all(|(a, e)| {
+                a.len() == e.len()
+                    && a.iter().zip(e).all(|(&(ax, ay), &(ex, ey))| {
+                        (ax - ex).abs() < 1e-9 && (ay - ey).abs() < 1e-9
+                    })
+            });
+        assert!(close, "{:?} != {:?}", actual, expected);
+    }
+
+    #[test]
+    fn relative_lines() {
+        assert_eq!(
+            path_strokes("m10 10 l10 0 h5 v5 l-5 0 z"),
+            [[
+                (10.0, 10.0),
+                (20.0, 10.0),
+                (25.0, 10.0),
+                (25.0, 15.0),
+                (20.0, 15.0),
+                (10.0, 10.0)
+            ]]
+        );
+    }
+
+    #[test]
+    fn implicit_commands() {
+        // arguments after a moveto are linetos, relative after `m`, and packed numbers need
+        // no separator
+        assert_eq!(
+            path_strokes("m0 0 10 0 0 10M5-5 5.5.5"),
+            [
+                vec![(0.0, 0.0), (10.0, 0.0), (10.0, 10.0)],
+                vec![(5.0, -5.0), (5.5, 0.5)]
+            ]
+        );
+        // drawing on after a closepath starts from the start of the closed subpath
+        assert_eq!(
+            path_strokes("m10 10 h10 z l0 5"),
+            [
+                vec![(10.0, 10.0), (20.0, 10.0), (10.0, 10.0)],
+                vec![(10.0, 10.0), (10.0, 15.0)]
+            ]
+        );
+    }
+
+    #[test]
+    fn relative_curves() {
+        let curve = |stroke: Stroke| coordinates(&[stroke]).remove(0);
+        let mut expected = curve(quadratic_bezier_stroke(
+            (10.0, 0.0).into(),
+            (20.0, 10.0).into(),
+            (30.0, 0.0).into(),
+        ));
+        expected.extend(
+            curve(cubic_bezier_stroke(
+                (30.0, 0.0).into(),
+                (30.0, 10.0).into(),
+                (40.0, 10.0).into(),
+                (40.0, 0.0).into(),
+            ))
+            .into_iter()
+            .skip(1),
+        );
+        // `s` reflects the second control point of the previous curve
+        expected.extend(
+            curve(cubic_bezier_stroke(
+                (40.0, 0.0).into(),
+                (40.0, -10.0).into(),
+                (50.0, -10.0).into(),
+                (50.0, 0.0).into(),
+            ))
+            .into_iter()
+            .skip(1),
+        );
+        assert_close(
+            path_strokes("M10 0 q10 10 20 0 c0 10 10 10 10 0 s10 -10 10 0"),
+            vec![expected],
+        );
+    }
+
+    #[test]
+    fn invalid_paths() {
+        assert_eq!(
+            strokes_from_svg(&svg("", r#"<path d="M0 0 A5 5 0 0 1 10 0"/>"#)).unwrap_err(),
+            SvgError::UnsupportedPathCommand { command: 'A' }
+        );
+        assert_eq!(
+            strokes_from_svg(&svg("", r#"<path d="M0 0 L10"/>"#)).unwrap_err(),
+            SvgError::InvalidAttribute {
+                element: "path".to_string(),
+                attribute: "d"
+            }
+        );
+    }
+
+    #[test]
+    fn transforms() {
+        let strokes = strokes_from_svg(&svg(
+            "",
+            r#"<g transform="translate(10 20)">
+                 <path transform="scale(2)" d="M1 1 L2 3"/>
+                 <g transform="rotate(90)"><line x2="1"/></g>
+               </g>
+               <polyline transform="rotate(90 10 10)" points="10,10 20,10"/>
+               <polygon transform="translate(5) scale(2, 3)" points="0,0 1,0 1,1"/>
+               <line transform="matrix(0 1 -1 0 5 6)" x1="1" y1="2" x2="3" y2="4"/>
+               <defs><line x2="1"/></defs>"#,
+        ))
+        .unwrap();
+        assert_close(
+            coordinates(&strokes),
+            vec![
+                vec![(12.0, 22.0), (14.0, 26.0)],
+                vec![(10.0, 20.0), (10.0, 21.0)],
+                vec![(10.0, 10.0), (10.0, 20.0)],
+                vec![(5.0, 0.0), (7.0, 0.0), (7.0, 3.0), (5.0, 0.0)],
+                vec![(3.0, 7.0), (1.0, 9.0)],
+            ],
+        );
+        assert_eq!(
+            strokes_from_svg(&svg("", r#"<path transform="spin(1)" d="M0 0 L1 1"/>"#)).unwrap_err(),
+            SvgError::InvalidAttribute {
+                element: "path".to_string(),
+                attribute: "transform"
+            }
+        );
+    }
+
+    #[test]
+    fn root_size() {
+        let canvas = |attributes| drawing_from_svg(&svg(attributes, "")).map(|d| d.canvas);
+        assert_eq!(
+            canvas(r#"viewBox="-10 -20 300 400" width="10mm""#),
+            Ok(CanvasSpec {
+                width: 300.0,
+                height: 400.0,
+                origin_x: -10.0,
+                origin_y: -20.0
+            })
+        );
+        assert_eq!(
+            canvas(r#"width="50px" height="40""#),
+            Ok(CanvasSpec {
+                width: 50.0,
+                height: 40.0,
+                ..CanvasSpec::GLYPHWIKI
+            })
+        );
+        // sizes in other units do not give the user coordinates
+        assert_eq!(
+            canvas(r#"width="100%" height="100%""#),
+            Ok(CanvasSpec::GLYPHWIKI)
+        );
+        assert_eq!(
+            canvas(r#"width="10mm" height="10mm""#),
+            Ok(CanvasSpec::GLYPHWIKI)
+        );
+        assert_eq!(canvas(""), Ok(CanvasSpec::GLYPHWIKI));
+        assert_eq!(
+            canvas(r#"width="0" height="40""#),
+            Err(SvgError::InvalidAttribute {
+                element: "svg".to_string(),
+                attribute: "width"
+            })
+        );
+        assert_eq!(
+            canvas(r#"viewBox="0 0 100""#),
+            Err(SvgError::InvalidAttribute {
+                element: "svg".to_string(),
+                attribute: "viewBox"
+            })
+        );
+    }
+}